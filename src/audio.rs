@@ -0,0 +1,89 @@
+/// Something that makes a noise in the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sound {
+    Shoot,
+    Bounce,
+    Hit,
+    Jump,
+    Death,
+    RoundEnd,
+}
+
+/// Plays sounds on an output device.
+pub trait Backend {
+    /// Plays `sound` with the given volume for the left and right channel.
+    fn play(&mut self, sound: Sound, left: f32, right: f32);
+}
+
+/// Swallows all sounds, for running without an audio device.
+#[derive(Default)]
+pub struct NullBackend {}
+
+impl Backend for NullBackend {
+    fn play(&mut self, _sound: Sound, _left: f32, _right: f32) {}
+}
+
+/// Picks the default output device if the game was built with audio,
+/// falling back to silence if there is none.
+pub fn default_backend() -> Box<dyn Backend> {
+    #[cfg(feature = "audio")]
+    {
+        match RodioBackend::new() {
+            Ok(backend) => return Box::new(backend),
+            Err(e) => eprintln!("no audio output: {}", e),
+        }
+    }
+    Box::new(NullBackend::default())
+}
+
+/// Synthesizes short tones on the default output device.
+#[cfg(feature = "audio")]
+pub struct RodioBackend {
+    // the stream stops playing when dropped
+    _stream: rodio::OutputStream,
+    handle: rodio::OutputStreamHandle,
+    /// Set after the first sound failed to play, the device is not tried again.
+    failed: bool,
+}
+
+#[cfg(feature = "audio")]
+impl RodioBackend {
+    pub fn new() -> Result<Self, rodio::StreamError> {
+        let (stream, handle) = rodio::OutputStream::try_default()?;
+        Ok(Self {
+            _stream: stream,
+            handle,
+            failed: false,
+        })
+    }
+}
+
+#[cfg(feature = "audio")]
+impl Backend for RodioBackend {
+    fn play(&mut self, sound: Sound, left: f32, right: f32) {
+        use rodio::source::{ChannelVolume, SineWave, Source};
+        use std::time::Duration;
+
+        if self.failed {
+            return;
+        }
+
+        // pitch in Hz and length in seconds
+        let (pitch, length) = match sound {
+            Sound::Shoot => (880.0, 0.05),
+            Sound::Bounce => (1320.0, 0.03),
+            Sound::Hit => (220.0, 0.1),
+            Sound::Jump => (520.0, 0.06),
+            Sound::Death => (110.0, 0.35),
+            Sound::RoundEnd => (660.0, 0.6),
+        };
+        let tone = SineWave::new(pitch)
+            .take_duration(Duration::from_secs_f32(length))
+            .fade_in(Duration::from_millis(5));
+        let source = ChannelVolume::new(tone, vec![left, right]);
+        if let Err(e) = self.handle.play_raw(source) {
+            eprintln!("could not play {:?}, muting all sounds: {}", sound, e);
+            self.failed = true;
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::audio::{Backend, Sound};
+use crate::camera::Camera;
+use crate::shot;
+use crate::{GameController, PlayerController, ShotController};
+use piston::input::GenericEvent;
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::FRAC_PI_4;
+
+/// Upward speed a player has to gain in one tick to count as a jump.
+const JUMP_IMPULSE: f64 = 300.0;
+
+pub struct AudioSettings {
+    /// Loudness of all sounds, from 0 for silence to 1.
+    pub volume: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self { volume: 0.8 }
+    }
+}
+
+impl AudioSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Plays sounds for changes of the game state, panned by where on the
+/// screen they happen. Like the effects, this works the same on hosts and
+/// clients without anything being sent over the network.
+pub struct AudioController {
+    pub settings: AudioSettings,
+    backend: Box<dyn Backend>,
+    /// Lives of each shot in the previous tick.
+    shots: HashMap<shot::Id, u32>,
+    /// Lives and vertical speed of each player in the previous tick.
+    players: HashMap<String, (u8, f64)>,
+    round: Option<u32>,
+}
+
+impl AudioController {
+    pub fn new(settings: AudioSettings, backend: Box<dyn Backend>) -> Self {
+        Self {
+            settings,
+            backend,
+            shots: HashMap::new(),
+            players: HashMap::new(),
+            round: None,
+        }
+    }
+
+    pub fn event<E: GenericEvent>(
+        &mut self,
+        e: &E,
+        game_controller: &GameController,
+        player_controller: &PlayerController,
+        shot_controller: &ShotController,
+        camera: &Camera,
+    ) {
+        if e.update_args().is_none() {
+            return;
+        }
+
+        let mut sounds = vec![];
+
+        // one shot sound per player, even for a spread of pellets
+        let mut shooters = HashSet::new();
+        for (id, shot) in &shot_controller.shots {
+            let x = shot.state.x + shot.w / 2.0;
+            match self.shots.get(id) {
+                None => {
+                    if shooters.insert(&id.owner) {
+                        sounds.push((Sound::Shoot, x));
+                    }
+                }
+                Some(&lives) if shot.state.lives < lives && shot.state.lives > 0 => {
+                    sounds.push((Sound::Bounce, x));
+                }
+                Some(_) => {}
+            }
+        }
+        self.shots = shot_controller
+            .shots
+            .iter()
+            .map(|(id, shot)| (id.clone(), shot.state.lives))
+            .collect();
+
+        for (name, player) in &player_controller.players {
+            let state = &player.state;
+            let x = state.x + state.width / 2.0;
+            if let Some(&(lives, dy)) = self.players.get(name) {
+                if state.lives < lives {
+                    let sound = if state.lives == 0 {
+                        Sound::Death
+                    } else {
+                        Sound::Hit
+                    };
+                    sounds.push((sound, x));
+                } else if state.lives > 0 && !player.is_dashing() && state.dy < dy - JUMP_IMPULSE {
+                    sounds.push((Sound::Jump, x));
+                }
+            }
+        }
+        self.players = player_controller
+            .players
+            .iter()
+            .map(|(name, player)| (name.clone(), (player.state.lives, player.state.dy)))
+            .collect();
+
+        let round = game_controller.state.round;
+        if self.round.is_some_and(|previous| round > previous) {
+            sounds.push((Sound::RoundEnd, camera.x));
+        }
+        self.round = Some(round);
+
+        for (sound, x) in sounds {
+            self.play(sound, x, camera);
+        }
+    }
+
+    /// Plays a sound made at the horizontal world position `x`, louder on
+    /// the side of the screen it is on.
+    pub fn play(&mut self, sound: Sound, x: f64, camera: &Camera) {
+        let volume = self.settings.volume;
+        if volume <= 0.0 {
+            return;
+        }
+
+        let half_width = camera.visible()[0] / 2.0;
+        let pan = ((x - camera.x) / half_width).clamp(-1.0, 1.0) as f32;
+        // constant power panning, both channels at 0.7 in the center
+        let angle = (pan + 1.0) * FRAC_PI_4;
+        self.backend
+            .play(sound, volume * angle.cos(), volume * angle.sin());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game;
+    use crate::rules::Rules;
+    use crate::weapon::Weapon;
+    use crate::{Player, Shot};
+    use piston::input::{Event, Loop, UpdateArgs};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    type Played = Rc<RefCell<Vec<(Sound, f32, f32)>>>;
+
+    /// Remembers what it was asked to play.
+    struct Recorder(Played);
+
+    impl Backend for Recorder {
+        fn play(&mut self, sound: Sound, left: f32, right: f32) {
+            self.0.borrow_mut().push((sound, left, right));
+        }
+    }
+
+    struct Scene {
+        audio: AudioController,
+        played: Played,
+        game_controller: GameController,
+        player_controller: PlayerController,
+        shot_controller: ShotController,
+        camera: Camera,
+    }
+
+    impl Scene {
+        fn new() -> Self {
+            let played = Played::default();
+            let settings = AudioSettings { volume: 1.0 };
+            Self {
+                audio: AudioController::new(settings, Box::new(Recorder(played.clone()))),
+                played,
+                game_controller: GameController::new(game::State::default(), Rules::default()),
+                player_controller: PlayerController::new(),
+                shot_controller: ShotController::new(false),
+                camera: Camera::default(),
+            }
+        }
+
+        /// Runs a tick and returns the sounds played in it.
+        fn tick(&mut self) -> Vec<Sound> {
+            let tick = Event::Loop(Loop::Update(UpdateArgs { dt: 1.0 / 60.0 }));
+            self.audio.event(
+                &tick,
+                &self.game_controller,
+                &self.player_controller,
+                &self.shot_controller,
+                &self.camera,
+            );
+            self.played
+                .borrow_mut()
+                .drain(..)
+                .map(|(sound, ..)| sound)
+                .collect()
+        }
+
+        fn add_shot(&mut self, id: u32, owner: &str) {
+            let id = shot::Id {
+                id,
+                owner: owner.to_string(),
+            };
+            let shot = Shot::new(0.0, 0.0, 0.0, 0.0, id.clone(), Weapon::Shotgun, [1.0; 4]);
+            self.shot_controller.shots.insert(id, shot);
+        }
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn one_shoot_sound_per_volley() {
+        let mut scene = Scene::new();
+        for id in 0..5 {
+            scene.add_shot(id, "a");
+        }
+        scene.add_shot(5, "b");
+        assert_eq!(scene.tick(), vec![Sound::Shoot, Sound::Shoot]);
+        assert!(scene.tick().is_empty());
+    }
+
+    #[test]
+    fn bounces_hits_and_deaths_are_told_apart() {
+        let mut scene = Scene::new();
+        scene.add_shot(0, "a");
+        for name in &["a", "b"] {
+            let player = Player::new(name.to_string(), 0.0, 0.0, [1.0; 4], 3);
+            scene
+                .player_controller
+                .players
+                .insert(name.to_string(), player);
+        }
+        scene.tick();
+
+        let shot = scene.shot_controller.shots.values_mut().next().unwrap();
+        shot.state.lives -= 1;
+        assert_eq!(scene.tick(), vec![Sound::Bounce]);
+
+        // a shot running out of lives is gone, not bouncing
+        let shot = scene.shot_controller.shots.values_mut().next().unwrap();
+        shot.state.lives = 0;
+        assert!(scene.tick().is_empty());
+
+        scene
+            .player_controller
+            .players
+            .get_mut("a")
+            .unwrap()
+            .state
+            .lives = 2;
+        assert_eq!(scene.tick(), vec![Sound::Hit]);
+        scene
+            .player_controller
+            .players
+            .get_mut("b")
+            .unwrap()
+            .state
+            .lives = 0;
+        assert_eq!(scene.tick(), vec![Sound::Death]);
+    }
+
+    #[test]
+    fn round_end_plays_when_the_round_advances() {
+        let mut scene = Scene::new();
+        scene.game_controller.state.round = 3;
+        // joining mid-game is not the end of a round
+        assert!(scene.tick().is_empty());
+        scene.game_controller.state.round += 1;
+        assert_eq!(scene.tick(), vec![Sound::RoundEnd]);
+        assert!(scene.tick().is_empty());
+    }
+
+    #[test]
+    fn panning_keeps_the_power_constant() {
+        let mut scene = Scene::new();
+        scene.camera.x = 1000.0;
+        let half_width = scene.camera.visible()[0] / 2.0;
+        let edge = std::f32::consts::FRAC_1_SQRT_2;
+
+        let cases = [
+            (1000.0 - half_width, 1.0, 0.0),
+            (1000.0, edge, edge),
+            (1000.0 + half_width, 0.0, 1.0),
+            // beyond the screen stays at the edge
+            (1000.0 + 3.0 * half_width, 0.0, 1.0),
+        ];
+        for &(x, left, right) in cases.iter() {
+            scene.audio.play(Sound::Hit, x, &scene.camera);
+            let (_, played_left, played_right) = scene.played.borrow_mut().pop().unwrap();
+            assert_close(played_left, left);
+            assert_close(played_right, right);
+            assert_close(played_left.powi(2) + played_right.powi(2), 1.0);
+        }
+    }
+}
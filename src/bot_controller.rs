@@ -0,0 +1,360 @@
+use crate::entity::Bounds;
+use crate::game;
+use crate::game::Mode;
+use crate::pathfinder::{Move, Pathfinder, Step};
+use crate::player::Inputs;
+use crate::rules::Rules;
+use crate::{FlagController, Map, Player, PlayerController};
+use piston::input::GenericEvent;
+use rand::Rng;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Time between route searches.
+const REPLAN_INTERVAL: f64 = 0.5;
+/// Time between searches for bank shots, which are expensive.
+const BANK_SHOT_INTERVAL: f64 = 0.3;
+/// Distance of the samples when tracing lines and shot paths.
+const TRACE_STEP: f64 = 8.0;
+
+/// How well bots play, derived from a level between 0 and 1.
+#[derive(Clone, Copy, Debug)]
+pub struct Skill {
+    /// Time a target has to be in sight before the bot shoots.
+    pub reaction_time: f64,
+    /// Maximum aiming error in radians.
+    pub spread: f64,
+    /// Whether the bot aims where moving targets will be.
+    pub lead: bool,
+    /// Walls a shot may bounce off to reach a target out of sight.
+    pub bank_shots: u8,
+}
+
+impl Skill {
+    pub fn new(level: f64) -> Self {
+        let level = level.clamp(0.0, 1.0);
+        Self {
+            reaction_time: 0.8 - 0.6 * level,
+            spread: 0.3 * (1.0 - level),
+            lead: level > 0.3,
+            bank_shots: if level >= 0.5 { 1 } else { 0 },
+        }
+    }
+}
+
+#[derive(Default)]
+struct Bot {
+    path: Vec<Step>,
+    replan: f64,
+    /// Time the current target has been in sight.
+    seen: f64,
+    bank_search: f64,
+    bank_angle: Option<f64>,
+}
+
+/// Steers server-side bots, which are regular players whose inputs are set
+/// here instead of by a client.
+pub struct BotController {
+    bots: HashMap<String, Bot>,
+    skill: Skill,
+    pathfinder: Option<Pathfinder>,
+}
+
+impl BotController {
+    pub fn new(skill: Skill) -> Self {
+        Self {
+            bots: HashMap::new(),
+            skill,
+            pathfinder: None,
+        }
+    }
+
+    pub fn add(&mut self, name: String) {
+        self.bots.insert(name, Bot::default());
+    }
+
+    pub fn event<E: GenericEvent>(
+        &mut self,
+        map: &Map,
+        game: &game::State,
+        rules: &Rules,
+        flag_controller: &FlagController,
+        player_controller: &mut PlayerController,
+        e: &E,
+    ) {
+        if let Some(tick) = e.update_args() {
+            if self.pathfinder.as_ref().is_none_or(|p| p.is_outdated(map)) {
+                self.pathfinder = Some(Pathfinder::new(map));
+            }
+            let pathfinder = self.pathfinder.as_ref().unwrap();
+
+            // positions of everyone alive, to pick targets from
+            let targets: Vec<_> = player_controller
+                .players
+                .values()
+                .filter(|player| player.state.lives > 0)
+                .map(|player| {
+                    let (x, y) = center(player);
+                    let velocity = (player.state.dx, player.state.dy);
+                    (game.side(&player.state), (x, y), velocity)
+                })
+                .collect();
+
+            for (name, bot) in &mut self.bots {
+                let player = match player_controller.players.get_mut(name) {
+                    Some(player) => player,
+                    None => continue,
+                };
+                let before = player.inputs.clone();
+                if player.state.lives == 0 {
+                    player.inputs = Inputs {
+                        weapon: before.weapon,
+                        ..Inputs::default()
+                    };
+                } else {
+                    let side = game.side(&player.state);
+                    let enemy = targets
+                        .iter()
+                        .filter(|(other, ..)| *other != side)
+                        .min_by(|(_, a, _), (_, b, _)| {
+                            distance(center(player), *a)
+                                .partial_cmp(&distance(center(player), *b))
+                                .unwrap()
+                        })
+                        .map(|&(_, position, velocity)| (position, velocity));
+
+                    let goal = Self::goal(game, flag_controller, player)
+                        .or_else(|| enemy.map(|(position, _)| position));
+                    bot.navigate(map, pathfinder, player, goal, tick.dt);
+                    bot.aim(map, &self.skill, rules, player, enemy, tick.dt);
+                }
+
+                if player.inputs != before {
+                    player.dirty = true;
+                }
+            }
+        }
+    }
+
+    /// Where the mode wants the bot to be, if not hunting the closest enemy.
+    fn goal(
+        game: &game::State,
+        flag_controller: &FlagController,
+        player: &Player,
+    ) -> Option<(f64, f64)> {
+        match game.mode {
+            Mode::KingOfTheHill => game.zone.map(|[x, y, w, h]| (x + w / 2.0, y + h / 2.0)),
+            Mode::CaptureTheFlag => {
+                let team = player.state.team?;
+                let flags = flag_controller.flags.values();
+                let flag = if player.state.carrying_flag {
+                    flags.clone().find(|flag| flag.state.team == team)
+                } else {
+                    flags
+                        .clone()
+                        .find(|flag| flag.state.team != team && flag.state.carrier.is_none())
+                };
+                flag.map(|flag| {
+                    if player.state.carrying_flag {
+                        (flag.state.home_x, flag.state.home_y)
+                    } else {
+                        center(flag)
+                    }
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Bot {
+    fn navigate(
+        &mut self,
+        map: &Map,
+        pathfinder: &Pathfinder,
+        player: &mut Player,
+        goal: Option<(f64, f64)>,
+        dt: f64,
+    ) {
+        player.inputs.left = false;
+        player.inputs.right = false;
+        player.inputs.jump = false;
+
+        let goal = match goal {
+            Some(goal) => goal,
+            None => return,
+        };
+        let (x, _) = center(player);
+        let bottom = player.state.y + player.state.height - 1.0;
+        let position = map.coords_at(x, bottom);
+
+        self.replan -= dt;
+        if player.on_ground && self.replan <= 0.0 {
+            self.replan = REPLAN_INTERVAL;
+            let from = pathfinder.landing(position);
+            let to = pathfinder.landing(map.coords_at(goal.0, goal.1));
+            self.path = match (from, to) {
+                (Some(from), Some(to)) => pathfinder.find(from, to).unwrap_or_default(),
+                _ => vec![],
+            };
+        }
+
+        if player.on_ground {
+            self.path.retain(|step| step.node != position);
+        }
+        let (target_x, step) = match self.path.first() {
+            Some(step) => {
+                let cell = map.cell_bounds(step.node.0, step.node.1);
+                (cell.x + cell.w / 2.0, Some(*step))
+            }
+            // no route, head straight for the goal
+            None => (goal.0, None),
+        };
+
+        if target_x < x - 4.0 {
+            player.inputs.left = true;
+        } else if target_x > x + 4.0 {
+            player.inputs.right = true;
+        }
+
+        let inputs = &mut player.inputs;
+        let climbing =
+            step.is_some_and(|step| step.kind == Move::Jump && step.node.1 <= position.1);
+        if climbing && player.on_ground {
+            inputs.jump = true;
+        } else if let Some(step) = step.filter(|_| climbing) {
+            // jump again at the top if the first jump falls short
+            let target_bottom = map.cell_bounds(step.node.0, step.node.1);
+            let below = bottom > target_bottom.y + target_bottom.h;
+            inputs.jump = below && player.state.dy >= 0.0 && player.has_double_jump;
+        } else if player.on_wall.is_some() && !player.on_ground {
+            inputs.jump = true;
+        }
+    }
+
+    fn aim(
+        &mut self,
+        map: &Map,
+        skill: &Skill,
+        rules: &Rules,
+        player: &mut Player,
+        enemy: Option<((f64, f64), (f64, f64))>,
+        dt: f64,
+    ) {
+        let from = center(player);
+        let ((target_x, target_y), (dx, dy)) = match enemy {
+            Some(enemy) => enemy,
+            None => {
+                self.seen = 0.0;
+                player.inputs.shoot = false;
+                return;
+            }
+        };
+
+        let speed = player.inputs.weapon.speed() * rules.shot_speed;
+        let mut angle = if line_of_sight(map, from, (target_x, target_y)) {
+            let (mut x, mut y) = (target_x, target_y);
+            if skill.lead {
+                let time = distance(from, (x, y)) / speed;
+                x += dx * time;
+                y += dy * time;
+            }
+            Some((y - from.1).atan2(x - from.0))
+        } else if skill.bank_shots > 0 {
+            self.bank_search -= dt;
+            if self.bank_search <= 0.0 {
+                self.bank_search = BANK_SHOT_INTERVAL;
+                self.bank_angle = bank_shot(map, from, (target_x, target_y), skill.bank_shots);
+            }
+            self.bank_angle
+        } else {
+            None
+        };
+
+        match angle.as_mut() {
+            Some(angle) => {
+                self.seen += dt;
+                if skill.spread > 0.0 {
+                    *angle += rand::thread_rng().gen_range(-skill.spread, skill.spread);
+                }
+            }
+            None => self.seen = 0.0,
+        }
+
+        if let Some(angle) = angle {
+            player.inputs.mouse_x = from.0 + angle.cos() * 200.0;
+            player.inputs.mouse_y = from.1 + angle.sin() * 200.0;
+        }
+        player.inputs.shoot =
+            angle.is_some() && self.seen >= skill.reaction_time && player.ammo.can_fire();
+    }
+}
+
+fn center<B: Bounds>(b: &B) -> (f64, f64) {
+    (b.x() + b.w() / 2.0, b.y() + b.h() / 2.0)
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+fn blocks_shots(map: &Map, x: f64, y: f64) -> bool {
+    map.tile_at(x, y).is_none_or(|tile| tile.blocks_shots())
+}
+
+fn line_of_sight(map: &Map, from: (f64, f64), to: (f64, f64)) -> bool {
+    let steps = (distance(from, to) / TRACE_STEP).ceil() as usize;
+    (1..steps).all(|i| {
+        let t = i as f64 / steps as f64;
+        !blocks_shots(
+            map,
+            from.0 + (to.0 - from.0) * t,
+            from.1 + (to.1 - from.1) * t,
+        )
+    })
+}
+
+/// Traces shots in all directions, bouncing off walls like bolts do, and
+/// returns the angle of one that reaches the target after a bounce without
+/// coming back to the shooter.
+fn bank_shot(map: &Map, from: (f64, f64), to: (f64, f64), bounces: u8) -> Option<f64> {
+    const DIRECTIONS: usize = 120;
+    const MAX_STEPS: usize = 400;
+
+    (0..DIRECTIONS)
+        .map(|i| i as f64 / DIRECTIONS as f64 * 2.0 * PI)
+        .find(|&angle| {
+            let (mut x, mut y) = from;
+            let (mut dx, mut dy) = (angle.cos(), angle.sin());
+            let mut bounced = 0;
+
+            for _ in 0..MAX_STEPS {
+                let (nx, ny) = (x + dx * TRACE_STEP, y + dy * TRACE_STEP);
+                if blocks_shots(map, nx, ny) {
+                    if bounced == bounces {
+                        return false;
+                    }
+                    bounced += 1;
+                    let flip_x = blocks_shots(map, nx, y);
+                    let flip_y = blocks_shots(map, x, ny);
+                    if flip_x || !flip_y {
+                        dx = -dx;
+                    }
+                    if flip_y || !flip_x {
+                        dy = -dy;
+                    }
+                    continue;
+                }
+
+                x = nx;
+                y = ny;
+                if bounced > 0 && distance((x, y), from) < 20.0 {
+                    return false;
+                }
+                if distance((x, y), to) < 15.0 {
+                    return bounced > 0;
+                }
+            }
+            false
+        })
+}
@@ -0,0 +1,70 @@
+use crate::map::CELL_SIZE;
+
+/// Cells shown across the window at zoom 1, a whole map of the default size.
+const VIEW_CELLS: [f64; 2] = [48.0, 27.0];
+
+/// Part of the world shown in the window.
+#[derive(Debug)]
+pub struct Camera {
+    /// World position shown in the center of the window.
+    pub x: f64,
+    pub y: f64,
+    pub zoom: f64,
+    /// Size of the window in pixels.
+    pub window: [f64; 2],
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+            window: [1920.0, 1080.0],
+        }
+    }
+}
+
+impl Camera {
+    /// Pixels per world unit without zoom, used for overlays that don't zoom.
+    pub fn base_scale(&self) -> f64 {
+        let [width, height] = self.window;
+        (width / (VIEW_CELLS[0] * CELL_SIZE)).min(height / (VIEW_CELLS[1] * CELL_SIZE))
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.base_scale() * self.zoom
+    }
+
+    /// Size of the visible part of the world in world units.
+    pub fn visible(&self) -> [f64; 2] {
+        let scale = self.scale();
+        [self.window[0] / scale, self.window[1] / scale]
+    }
+
+    /// Scale and translation from world to window coordinates.
+    pub fn scaling(&self) -> (f64, f64, f64) {
+        let scale = self.scale();
+        (
+            scale,
+            self.window[0] / 2.0 - self.x * scale,
+            self.window[1] / 2.0 - self.y * scale,
+        )
+    }
+
+    /// Keeps the view inside a world of the given size, centering it on axes
+    /// where the whole world fits into the window.
+    pub fn clamp(&mut self, world: [f64; 2]) {
+        let [width, height] = self.visible();
+        self.x = clamp_axis(self.x, width, world[0]);
+        self.y = clamp_axis(self.y, height, world[1]);
+    }
+}
+
+fn clamp_axis(center: f64, visible: f64, world: f64) -> f64 {
+    if visible >= world {
+        world / 2.0
+    } else {
+        center.clamp(visible / 2.0, world - visible / 2.0)
+    }
+}
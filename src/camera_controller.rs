@@ -0,0 +1,61 @@
+use crate::camera::Camera;
+use crate::entity::Bounds;
+use crate::{Map, PlayerController};
+use piston::input::GenericEvent;
+
+/// Part of the visible area around its center the followed player can move
+/// in without the camera moving.
+const DEAD_ZONE: f64 = 0.15;
+/// How fast the camera catches up with the player, higher is faster.
+const SMOOTHING: f64 = 6.0;
+const ZOOM_STEP: f64 = 1.1;
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 2.0;
+
+/// Follows the local player and zooms with the mouse wheel.
+#[derive(Default)]
+pub struct CameraController {
+    pub camera: Camera,
+}
+
+impl CameraController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn event<E: GenericEvent>(
+        &mut self,
+        e: &E,
+        window: [f64; 2],
+        map: &Map,
+        player_controller: &PlayerController,
+        local_player: Option<&str>,
+    ) {
+        let camera = &mut self.camera;
+        camera.window = window;
+
+        if let Some([_, scroll]) = e.mouse_scroll_args() {
+            camera.zoom = (camera.zoom * ZOOM_STEP.powf(scroll)).clamp(MIN_ZOOM, MAX_ZOOM);
+        }
+
+        if let Some(tick) = e.update_args() {
+            let target = local_player
+                .and_then(|name| player_controller.players.get(name))
+                .filter(|player| player.state.lives > 0);
+            if let Some(player) = target {
+                let target_x = player.x() + player.w() / 2.0;
+                let target_y = player.y() + player.h() / 2.0;
+                let [width, height] = camera.visible();
+                let (zone_x, zone_y) = (width * DEAD_ZONE / 2.0, height * DEAD_ZONE / 2.0);
+                // the closest position that has the player inside the dead zone
+                let x = target_x - (target_x - camera.x).clamp(-zone_x, zone_x);
+                let y = target_y - (target_y - camera.y).clamp(-zone_y, zone_y);
+
+                let t = 1.0 - (-SMOOTHING * tick.dt).exp();
+                camera.x += (x - camera.x) * t;
+                camera.y += (y - camera.y) * t;
+            }
+            camera.clamp(map.world_size());
+        }
+    }
+}
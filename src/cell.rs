@@ -1,12 +1,13 @@
 use crate::entity::Bounds;
+use crate::tile::Tile;
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Cell {
     pub x: f64,
     pub y: f64,
     pub w: f64,
     pub h: f64,
-    pub state: bool,
+    pub tile: Tile,
 }
 
 impl Bounds for Cell {
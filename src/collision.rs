@@ -1,39 +1,333 @@
 use crate::entity::{Bounds, Speed};
 
+/// Something an entity ran into, either a map cell or a moving platform.
 #[derive(Debug)]
-pub enum Collision<'a, B: Bounds> {
-    Side { x: Option<&'a B>, y: Option<&'a B> },
-    Corner { cell: &'a B },
+pub enum Obstacle<'a, A, B> {
+    Cell(&'a A),
+    Platform(&'a B),
 }
 
-pub fn check<'a, E: Bounds + Speed, B: Bounds + 'a>(
+impl<'a, A, B> Obstacle<'a, A, B> {
+    pub fn cell(&self) -> Option<&'a A> {
+        match *self {
+            Obstacle::Cell(cell) => Some(cell),
+            Obstacle::Platform(_) => None,
+        }
+    }
+
+    pub fn platform(&self) -> Option<&'a B> {
+        match *self {
+            Obstacle::Cell(_) => None,
+            Obstacle::Platform(platform) => Some(platform),
+        }
+    }
+}
+
+impl<'a, A: Bounds, B: Bounds> Bounds for Obstacle<'a, A, B> {
+    fn x(&self) -> f64 {
+        match self {
+            Obstacle::Cell(cell) => cell.x(),
+            Obstacle::Platform(platform) => platform.x(),
+        }
+    }
+    fn y(&self) -> f64 {
+        match self {
+            Obstacle::Cell(cell) => cell.y(),
+            Obstacle::Platform(platform) => platform.y(),
+        }
+    }
+    fn w(&self) -> f64 {
+        match self {
+            Obstacle::Cell(cell) => cell.w(),
+            Obstacle::Platform(platform) => platform.w(),
+        }
+    }
+    fn h(&self) -> f64 {
+        match self {
+            Obstacle::Cell(cell) => cell.h(),
+            Obstacle::Platform(platform) => platform.h(),
+        }
+    }
+}
+
+/// The first contact of a moving entity with an obstacle.
+#[derive(Debug)]
+pub struct Impact<'a, A, B> {
+    /// Fraction of the swept time until the contact, between 0 and 1.
+    pub time: f64,
+    /// Position of the entity at the contact, flush against the obstacle.
+    pub x: f64,
+    pub y: f64,
+    /// Unit normal of the surface that was hit, pointing away from it. It is
+    /// diagonal when a corner is hit exactly.
+    pub normal: [f64; 2],
+    pub obstacle: Obstacle<'a, A, B>,
+}
+
+impl<'a, A, B> Impact<'a, A, B> {
+    pub fn is_corner(&self) -> bool {
+        self.normal[0] != 0.0 && self.normal[1] != 0.0
+    }
+}
+
+/// Tolerance for entities that ended up slightly inside an obstacle due to
+/// rounding, as a fraction of the swept time.
+const EPSILON: f64 = 1e-6;
+
+/// Sweeps `entity` along its speed for `dt` against the static `cells` and
+/// the moving `platforms`, returning the earliest contact. Obstacles the
+/// entity already overlaps are ignored so it can't get stuck inside them.
+pub fn sweep<'a, E: Bounds + Speed, A: Bounds + 'a, B: Bounds + 'a>(
     entity: &E,
-    obstacles: &'a [B],
+    cells: &'a [A],
+    platforms: &'a [B],
     dt: f64,
-) -> Option<Collision<'a, B>> {
-    let new_x = entity.x() + entity.dx() * dt;
-    let new_y = entity.y() + entity.dy() * dt;
-
-    let moved_x = [new_x, entity.y(), entity.w(), entity.h()];
-    let collides_x = obstacles.iter().find(|&cell| collides(&moved_x, cell));
+) -> Option<Impact<'a, A, B>> {
+    let dx = entity.dx() * dt;
+    let dy = entity.dy() * dt;
 
-    let moved_y = [entity.x(), new_y, entity.w(), entity.h()];
-    let collides_y = obstacles.iter().find(|&cell| collides(&moved_y, cell));
+    cells
+        .iter()
+        .map(Obstacle::Cell)
+        .chain(platforms.iter().map(Obstacle::Platform))
+        .filter_map(|obstacle| {
+            let (entry_x, exit_x) = axis(entity.x(), entity.w(), dx, obstacle.x(), obstacle.w())?;
+            let (entry_y, exit_y) = axis(entity.y(), entity.h(), dy, obstacle.y(), obstacle.h())?;
+            let entry = entry_x.max(entry_y);
+            let exit = exit_x.min(exit_y);
+            if entry > exit || !(-EPSILON..1.0).contains(&entry) {
+                return None;
+            }
 
-    if collides_x.or(collides_y).is_some() {
-        Some(Collision::Side {
-            x: collides_x,
-            y: collides_y,
+            let mut normal = [
+                if entry_x >= entry_y {
+                    -dx.signum()
+                } else {
+                    0.0
+                },
+                if entry_y >= entry_x {
+                    -dy.signum()
+                } else {
+                    0.0
+                },
+            ];
+            if normal[0] != 0.0 && normal[1] != 0.0 {
+                normal = normal.map(|n| n * std::f64::consts::FRAC_1_SQRT_2);
+            }
+            let time = entry.max(0.0);
+            let x = match normal[0] {
+                n if n > 0.0 => obstacle.x() + obstacle.w(),
+                n if n < 0.0 => obstacle.x() - entity.w(),
+                _ => entity.x() + dx * time,
+            };
+            let y = match normal[1] {
+                n if n > 0.0 => obstacle.y() + obstacle.h(),
+                n if n < 0.0 => obstacle.y() - entity.h(),
+                _ => entity.y() + dy * time,
+            };
+            Some(Impact {
+                time,
+                x,
+                y,
+                normal,
+                obstacle,
+            })
+        })
+        // a corner touched at the same time as a face is the seam between two
+        // obstacles, the face is what was really hit
+        .min_by(|a, b| {
+            a.time
+                .total_cmp(&b.time)
+                .then(a.is_corner().cmp(&b.is_corner()))
         })
+}
+
+/// Reflects the speed `dx`, `dy` off a surface with the unit `normal`.
+pub fn reflect(dx: f64, dy: f64, normal: [f64; 2]) -> (f64, f64) {
+    let dot = dx * normal[0] + dy * normal[1];
+    (dx - 2.0 * dot * normal[0], dy - 2.0 * dot * normal[1])
+}
+
+/// Area covered by `entity` while moving along its speed for `dt`.
+pub fn swept_bounds<E: Bounds + Speed>(entity: &E, dt: f64) -> [f64; 4] {
+    let dx = entity.dx() * dt;
+    let dy = entity.dy() * dt;
+    [
+        entity.x() + dx.min(0.0),
+        entity.y() + dy.min(0.0),
+        entity.w() + dx.abs(),
+        entity.h() + dy.abs(),
+    ]
+}
+
+/// Times along one axis at which a span starting at `position` with size
+/// `size` moving by `delta` starts and stops overlapping the span of the
+/// obstacle, `None` if it never does.
+fn axis(position: f64, size: f64, delta: f64, start: f64, length: f64) -> Option<(f64, f64)> {
+    let end = start + length;
+    if delta > 0.0 {
+        Some(((start - position - size) / delta, (end - position) / delta))
+    } else if delta < 0.0 {
+        Some(((end - position) / delta, (start - position - size) / delta))
+    } else if position < end && position + size > start {
+        Some((f64::NEG_INFINITY, f64::INFINITY))
     } else {
-        let moved_xy = [new_x, new_y, entity.w(), entity.h()];
-        obstacles
-            .iter()
-            .find(|&cell| collides(&moved_xy, cell))
-            .map(|cell| Collision::Corner { cell })
+        None
     }
 }
 
 pub fn collides<A: Bounds, B: Bounds>(a: &A, b: &B) -> bool {
     a.x() < b.x() + b.w() && a.x() + a.w() > b.x() && a.y() < b.y() + b.h() && a.y() + a.h() > b.y()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::Cell;
+    use crate::platform::Platform;
+    use crate::tile::Tile;
+
+    struct Body {
+        bounds: [f64; 4],
+        speed: [f64; 2],
+    }
+
+    impl Bounds for Body {
+        fn x(&self) -> f64 {
+            self.bounds[0]
+        }
+        fn y(&self) -> f64 {
+            self.bounds[1]
+        }
+        fn w(&self) -> f64 {
+            self.bounds[2]
+        }
+        fn h(&self) -> f64 {
+            self.bounds[3]
+        }
+    }
+
+    impl Speed for Body {
+        fn dx(&self) -> f64 {
+            self.speed[0]
+        }
+        fn dy(&self) -> f64 {
+            self.speed[1]
+        }
+    }
+
+    const NO_PLATFORMS: [Platform; 0] = [];
+
+    fn solid(x: f64, y: f64) -> Cell {
+        Cell {
+            x,
+            y,
+            w: 40.0,
+            h: 40.0,
+            tile: Tile::Solid,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn fast_shot_stops_at_thin_wall_on_long_ticks() {
+        let cells = [solid(100.0, 0.0)];
+        for &dt in &[0.1, 0.2, 0.3, 0.4, 0.5] {
+            let shot = Body {
+                bounds: [50.0, 10.0, 15.0, 15.0],
+                speed: [800.0, 0.0],
+            };
+            // the end position alone would be past the cell from dt 0.16 on
+            let impact = sweep(&shot, &cells, &NO_PLATFORMS, dt).expect("shot tunneled");
+            assert_close(impact.time, 35.0 / (800.0 * dt));
+            assert_close(impact.x, 85.0);
+            assert_close(impact.y, 10.0);
+            assert_eq!(impact.normal, [-1.0, 0.0]);
+            assert!(impact.x + shot.w() <= cells[0].x);
+        }
+    }
+
+    #[test]
+    fn falling_player_lands_on_thin_floor_on_long_ticks() {
+        let cells = [solid(0.0, 100.0)];
+        for &dt in &[0.1, 0.2, 0.3, 0.4, 0.5] {
+            let player = Body {
+                bounds: [10.0, 0.0, 20.0, 20.0],
+                speed: [0.0, 1000.0],
+            };
+            let impact = sweep(&player, &cells, &NO_PLATFORMS, dt).expect("player tunneled");
+            assert_close(impact.time, 80.0 / (1000.0 * dt));
+            assert_close(impact.x, 10.0);
+            assert_close(impact.y, 80.0);
+            assert_eq!(impact.normal, [0.0, -1.0]);
+            assert!(impact.y + player.h() <= cells[0].y);
+        }
+    }
+
+    #[test]
+    fn nothing_is_hit_when_moving_past() {
+        let cells = [solid(100.0, 0.0)];
+        let shot = Body {
+            bounds: [50.0, 50.0, 15.0, 15.0],
+            speed: [800.0, 0.0],
+        };
+        assert!(sweep(&shot, &cells, &NO_PLATFORMS, 0.5).is_none());
+    }
+
+    #[test]
+    fn flat_wall_reflects_across_its_normal() {
+        let cells = [solid(100.0, 0.0), solid(100.0, 40.0)];
+        let shot = Body {
+            bounds: [70.0, 20.0, 15.0, 15.0],
+            speed: [300.0, 100.0],
+        };
+        let impact = sweep(&shot, &cells, &NO_PLATFORMS, 0.1).unwrap();
+        assert_eq!(impact.normal, [-1.0, 0.0]);
+        assert!(!impact.is_corner());
+        assert_eq!(reflect(300.0, 100.0, impact.normal), (-300.0, 100.0));
+    }
+
+    #[test]
+    fn exact_wall_end_hit_uses_a_diagonal_normal() {
+        let cells = [solid(100.0, 100.0)];
+        let shot = Body {
+            bounds: [75.0, 75.0, 15.0, 15.0],
+            speed: [100.0, 100.0],
+        };
+        let impact = sweep(&shot, &cells, &NO_PLATFORMS, 0.2).unwrap();
+        assert!(impact.is_corner());
+        assert_close(impact.time, 0.5);
+        let n = std::f64::consts::FRAC_1_SQRT_2;
+        assert_close(impact.normal[0], -n);
+        assert_close(impact.normal[1], -n);
+        let (dx, dy) = reflect(100.0, 50.0, impact.normal);
+        assert_close(dx, -50.0);
+        assert_close(dy, -100.0);
+    }
+
+    #[test]
+    fn face_wins_at_seam_between_cells() {
+        // the shot reaches the top of both cells exactly where they meet, in
+        // either order of the cells
+        let floor = [solid(0.0, 100.0), solid(40.0, 100.0)];
+        let reversed = [solid(40.0, 100.0), solid(0.0, 100.0)];
+        for cells in &[floor, reversed] {
+            let shot = Body {
+                bounds: [10.0, 70.0, 15.0, 15.0],
+                speed: [150.0, 150.0],
+            };
+            let impact = sweep(&shot, cells, &NO_PLATFORMS, 0.2).unwrap();
+            assert_close(impact.time, 0.5);
+            assert_eq!(impact.normal, [0.0, -1.0]);
+            assert_eq!(reflect(150.0, 150.0, impact.normal), (150.0, -150.0));
+        }
+    }
+}
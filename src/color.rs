@@ -0,0 +1,94 @@
+use anyhow::anyhow;
+
+/// Colors closer than this are considered indistinguishable.
+pub const MIN_DISTANCE: f32 = 0.2;
+
+/// Generates `n` well-distinguishable, fully opaque colors.
+///
+/// Hues are spread using the golden ratio so that any prefix of the palette is
+/// still evenly distributed, while brightness and saturation alternate to
+/// separate neighboring hues further.
+pub fn palette(n: usize) -> Vec<[f32; 4]> {
+    const GOLDEN_RATIO: f32 = 0.618_034;
+
+    (0..n)
+        .map(|i| {
+            let hue = (0.38 + i as f32 * GOLDEN_RATIO).fract();
+            let (saturation, value) = match i % 3 {
+                0 => (1.0, 1.0),
+                1 => (0.75, 0.85),
+                _ => (0.9, 0.65),
+            };
+            hsv_to_rgb(hue, saturation, value)
+        })
+        .collect()
+}
+
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 4] {
+    let h = hue.fract() * 6.0;
+    let c = value * saturation;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m = value - c;
+    let (r, g, b) = match h as u8 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m, 1.0]
+}
+
+/// Number of distinguishable shades `shade` makes of a color.
+pub const SHADES: usize = 4;
+
+/// Mixes white into `color`, a bit more for each `step` below `SHADES`, so
+/// members of a team share a hue but stay distinguishable.
+pub fn shade(color: [f32; 4], step: usize) -> [f32; 4] {
+    debug_assert!(step < SHADES);
+    let amount = 0.2 * step as f32;
+    let [r, g, b, a] = color;
+    [
+        r + (1.0 - r) * amount,
+        g + (1.0 - g) * amount,
+        b + (1.0 - b) * amount,
+        a,
+    ]
+}
+
+/// Makes a color received from a client safe to use: components are clamped
+/// to 0..1 and alpha is forced to 1. Returns `None` if any component is not
+/// finite.
+pub fn sanitize(color: [f32; 4]) -> Option<[f32; 4]> {
+    if !color.iter().all(|component| component.is_finite()) {
+        return None;
+    }
+    let [r, g, b, _] = color;
+    Some([r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0), 1.0])
+}
+
+/// Euclidean distance in rgb space, ignoring alpha.
+pub fn distance(a: &[f32; 4], b: &[f32; 4]) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .take(3)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Parses colors in the form `rrggbb` or `#rrggbb`.
+pub fn parse_hex(hex: &str) -> Result<[f32; 4], anyhow::Error> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(anyhow!("color must have the form rrggbb, got {:?}", hex));
+    }
+
+    let mut color = [1.0; 4];
+    for (i, component) in color.iter_mut().take(3).enumerate() {
+        let value = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)?;
+        *component = f32::from(value) / 255.0;
+    }
+    Ok(color)
+}
@@ -0,0 +1,207 @@
+use crate::particle::Particle;
+use crate::shot;
+use crate::{PlayerController, ShotController};
+use piston::input::GenericEvent;
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+
+/// Positions of a shot remembered for its trail.
+const TRAIL_LENGTH: usize = 8;
+pub const FLASH_TIME: f64 = 0.15;
+const PARTICLE_GRAVITY: f64 = 600.0;
+/// Screen shake in pixels per life lost by the local player.
+const SHAKE_PER_DAMAGE: f64 = 6.0;
+const MAX_SHAKE: f64 = 20.0;
+/// Pixels of shake that wear off per second.
+const SHAKE_FADE: f64 = 40.0;
+
+/// Which cosmetic effects are shown.
+pub struct EffectsSettings {
+    pub particles: bool,
+    pub trails: bool,
+    pub flashes: bool,
+    pub screen_shake: bool,
+}
+
+impl Default for EffectsSettings {
+    fn default() -> Self {
+        Self {
+            particles: true,
+            trails: true,
+            flashes: true,
+            screen_shake: true,
+        }
+    }
+}
+
+impl EffectsSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn none() -> Self {
+        Self {
+            particles: false,
+            trails: false,
+            flashes: false,
+            screen_shake: false,
+        }
+    }
+}
+
+pub struct Trail {
+    pub color: [f32; 4],
+    pub size: f64,
+    /// Centers of the shot, the newest first.
+    pub points: VecDeque<[f64; 2]>,
+}
+
+/// Derives cosmetic effects from changes of the game state, so it works the
+/// same on hosts and clients without anything being sent over the network.
+pub struct EffectsController {
+    pub settings: EffectsSettings,
+    pub particles: Vec<Particle>,
+    pub trails: HashMap<shot::Id, Trail>,
+    /// Remaining flash time of players that were just hit.
+    pub flashes: HashMap<String, f64>,
+    shake: f64,
+    /// Lives of each shot in the previous tick.
+    shots: HashMap<shot::Id, u32>,
+    /// Lives of each player in the previous tick.
+    lives: HashMap<String, u8>,
+}
+
+impl EffectsController {
+    pub fn new(settings: EffectsSettings) -> Self {
+        Self {
+            settings,
+            particles: vec![],
+            trails: HashMap::new(),
+            flashes: HashMap::new(),
+            shake: 0.0,
+            shots: HashMap::new(),
+            lives: HashMap::new(),
+        }
+    }
+
+    pub fn event<E: GenericEvent>(
+        &mut self,
+        e: &E,
+        player_controller: &PlayerController,
+        shot_controller: &ShotController,
+        local_player: Option<&str>,
+    ) {
+        if let Some(tick) = e.update_args() {
+            self.update_shots(shot_controller);
+            self.update_players(player_controller, local_player);
+            self.tick(tick.dt);
+        }
+    }
+
+    /// Random offset of the view while the local player is shaken.
+    pub fn shake_offset(&self) -> [f64; 2] {
+        if self.shake <= 0.0 {
+            return [0.0, 0.0];
+        }
+        let mut rng = rand::thread_rng();
+        [
+            rng.gen_range(-self.shake, self.shake),
+            rng.gen_range(-self.shake, self.shake),
+        ]
+    }
+
+    fn update_shots(&mut self, shot_controller: &ShotController) {
+        let mut shots = HashMap::new();
+        for (id, shot) in &shot_controller.shots {
+            let center = [shot.state.x + shot.w / 2.0, shot.state.y + shot.h / 2.0];
+            // bouncing and hitting something both cost the shot a life
+            let bounced = self
+                .shots
+                .get(id)
+                .is_some_and(|&lives| shot.state.lives < lives);
+            if bounced {
+                self.burst(center, shot.color, 6, 150.0);
+            }
+
+            if self.settings.trails {
+                let trail = self.trails.entry(id.clone()).or_insert_with(|| Trail {
+                    color: shot.color,
+                    size: shot.w,
+                    points: VecDeque::new(),
+                });
+                trail.points.push_front(center);
+                trail.points.truncate(TRAIL_LENGTH);
+            }
+            shots.insert(id.clone(), shot.state.lives);
+        }
+
+        self.trails.retain(|id, _| shots.contains_key(id));
+        self.shots = shots;
+    }
+
+    fn update_players(&mut self, player_controller: &PlayerController, local_player: Option<&str>) {
+        let mut lives = HashMap::new();
+        for (name, player) in &player_controller.players {
+            let state = &player.state;
+            let previous = self.lives.get(name).copied().unwrap_or(state.lives);
+            if state.lives < previous {
+                let center = [state.x + state.width / 2.0, state.y + state.height / 2.0];
+                if state.lives == 0 {
+                    self.burst(center, state.color, 40, 350.0);
+                } else {
+                    self.burst(center, state.color, 12, 200.0);
+                }
+                if self.settings.flashes {
+                    self.flashes.insert(name.clone(), FLASH_TIME);
+                }
+                if self.settings.screen_shake && local_player == Some(name.as_str()) {
+                    let damage = f64::from(previous - state.lives);
+                    self.shake = (self.shake + damage * SHAKE_PER_DAMAGE).min(MAX_SHAKE);
+                }
+            }
+            lives.insert(name.clone(), state.lives);
+        }
+        self.lives = lives;
+    }
+
+    fn tick(&mut self, dt: f64) {
+        for particle in &mut self.particles {
+            particle.dy += PARTICLE_GRAVITY * dt;
+            particle.x += particle.dx * dt;
+            particle.y += particle.dy * dt;
+            particle.life -= dt;
+        }
+        self.particles.retain(|particle| particle.life > 0.0);
+
+        for flash in self.flashes.values_mut() {
+            *flash -= dt;
+        }
+        self.flashes.retain(|_, flash| *flash > 0.0);
+
+        self.shake = (self.shake - SHAKE_FADE * dt).max(0.0);
+    }
+
+    /// Scatters `count` particles from `center` in all directions.
+    fn burst(&mut self, [x, y]: [f64; 2], color: [f32; 4], count: usize, speed: f64) {
+        if !self.settings.particles {
+            return;
+        }
+
+        let mut rng = rand::thread_rng();
+        self.particles.extend((0..count).map(|_| {
+            let angle = rng.gen_range(0.0, std::f64::consts::TAU);
+            let speed = speed * rng.gen_range(0.3, 1.0);
+            let life = rng.gen_range(0.2, 0.6);
+            Particle {
+                x,
+                y,
+                dx: speed * angle.cos(),
+                dy: speed * angle.sin(),
+                size: rng.gen_range(2.0, 5.0),
+                color,
+                life,
+                max_life: life,
+            }
+        }));
+    }
+}
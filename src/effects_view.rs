@@ -0,0 +1,65 @@
+use crate::effects_controller::FLASH_TIME;
+use crate::{EffectsController, PlayerController};
+use graphics::{Context, Graphics};
+
+#[derive(Default)]
+pub struct EffectsView {}
+
+impl EffectsView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn draw<G: Graphics>(
+        &self,
+        controller: &EffectsController,
+        player_controller: &PlayerController,
+        c: &Context,
+        g: &mut G,
+    ) {
+        use graphics::*;
+
+        // trails shrink and fade behind the shot
+        for trail in controller.trails.values() {
+            let count = trail.points.len() as f64;
+            for (i, &[x, y]) in trail.points.iter().enumerate().skip(1) {
+                let fade = 1.0 - i as f64 / count;
+                let size = trail.size * (0.3 + 0.5 * fade);
+                let mut color = trail.color;
+                color[3] *= 0.4 * fade as f32;
+                Ellipse::new(color).draw(
+                    [x - size / 2.0, y - size / 2.0, size, size],
+                    &c.draw_state,
+                    c.transform,
+                    g,
+                );
+            }
+        }
+
+        for (name, &flash) in &controller.flashes {
+            let player = match player_controller.players.get(name) {
+                Some(player) if player.state.lives > 0 => player,
+                _ => continue,
+            };
+            let state = &player.state;
+            let alpha = (flash / FLASH_TIME) as f32 * 0.8;
+            Rectangle::new_round([1.0, 1.0, 1.0, alpha], 5.0).draw(
+                [state.x, state.y, state.width, state.height],
+                &c.draw_state,
+                c.transform,
+                g,
+            );
+        }
+
+        for particle in &controller.particles {
+            let mut color = particle.color;
+            color[3] *= particle.fade();
+            Rectangle::new(color).draw(
+                [particle.x, particle.y, particle.size, particle.size],
+                &c.draw_state,
+                c.transform,
+                g,
+            );
+        }
+    }
+}
@@ -1,5 +1,3 @@
-use graphics::Context;
-
 pub trait Bounds {
     fn x(&self) -> f64;
     fn y(&self) -> f64;
@@ -8,15 +6,6 @@ pub trait Bounds {
     fn bounds(&self) -> [f64; 4] {
         [self.x(), self.y(), self.w(), self.h()]
     }
-    fn scale(&self, c: &Context) -> [f64; 4] {
-        let [w, h] = c.get_view_size();
-        [
-            self.x() / 1920.0 * w,
-            self.y() / 1080.0 * h,
-            self.w() / 1080.0 * w,
-            self.h() / 1080.0 * h,
-        ]
-    }
 }
 
 impl Bounds for [f64; 4] {
@@ -34,6 +23,21 @@ impl Bounds for [f64; 4] {
     }
 }
 
+impl<B: Bounds> Bounds for &B {
+    fn x(&self) -> f64 {
+        (*self).x()
+    }
+    fn y(&self) -> f64 {
+        (*self).y()
+    }
+    fn w(&self) -> f64 {
+        (*self).w()
+    }
+    fn h(&self) -> f64 {
+        (*self).h()
+    }
+}
+
 pub trait Speed {
     fn dx(&self) -> f64;
     fn dy(&self) -> f64;
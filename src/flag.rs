@@ -0,0 +1,75 @@
+use crate::entity::Bounds;
+use serde::{Deserialize, Serialize};
+
+pub const WIDTH: f64 = 20.0;
+pub const HEIGHT: f64 = 30.0;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct State {
+    pub team: u8,
+    pub x: f64,
+    pub y: f64,
+    pub home_x: f64,
+    pub home_y: f64,
+    /// Name of the player carrying the flag, it follows them while carried.
+    pub carrier: Option<String>,
+    /// Remaining time until a dropped flag returns to its base.
+    pub return_timer: f64,
+}
+
+impl State {
+    pub fn is_home(&self) -> bool {
+        self.carrier.is_none() && self.x == self.home_x && self.y == self.home_y
+    }
+}
+
+#[derive(Debug)]
+pub struct Flag {
+    pub state: State,
+    pub dirty: bool,
+}
+
+impl Flag {
+    pub fn new(team: u8, x: f64, y: f64) -> Self {
+        Self::from_state(State {
+            team,
+            x,
+            y,
+            home_x: x,
+            home_y: y,
+            carrier: None,
+            return_timer: 0.0,
+        })
+    }
+
+    pub fn from_state(state: State) -> Self {
+        Self { state, dirty: true }
+    }
+
+    pub fn return_home(&mut self) {
+        self.state.x = self.state.home_x;
+        self.state.y = self.state.home_y;
+        self.state.carrier = None;
+        self.state.return_timer = 0.0;
+        self.dirty = true;
+    }
+
+    pub fn home_bounds(&self) -> [f64; 4] {
+        [self.state.home_x, self.state.home_y, WIDTH, HEIGHT]
+    }
+}
+
+impl Bounds for Flag {
+    fn x(&self) -> f64 {
+        self.state.x
+    }
+    fn y(&self) -> f64 {
+        self.state.y
+    }
+    fn w(&self) -> f64 {
+        WIDTH
+    }
+    fn h(&self) -> f64 {
+        HEIGHT
+    }
+}
@@ -0,0 +1,138 @@
+use crate::collision;
+use crate::flag;
+use crate::flag::Flag;
+use crate::game::Mode;
+use crate::{GameController, Map, PlayerController};
+use piston::input::GenericEvent;
+use std::collections::HashMap;
+
+/// Time a dropped flag lies around before it returns to its base.
+const RETURN_TIME: f64 = 10.0;
+
+#[derive(Default)]
+pub struct FlagController {
+    /// Flags by team.
+    pub flags: HashMap<u8, Flag>,
+    /// Only the authoritative side moves flags and counts captures.
+    authoritative: bool,
+}
+
+impl FlagController {
+    pub fn new(authoritative: bool) -> Self {
+        Self {
+            authoritative,
+            ..Self::default()
+        }
+    }
+
+    pub fn event<E: GenericEvent>(
+        &mut self,
+        map: &Map,
+        game_controller: &mut GameController,
+        player_controller: &mut PlayerController,
+        e: &E,
+    ) {
+        if let Some(tick) = e.update_args() {
+            if !self.authoritative || game_controller.state.mode != Mode::CaptureTheFlag {
+                return;
+            }
+
+            if self.flags.is_empty() {
+                self.reset(map);
+            }
+
+            // a carrier scores by bringing the enemy flag to their own base,
+            // which only counts while their own flag is there too
+            let bases: HashMap<_, _> = self
+                .flags
+                .values()
+                .filter(|flag| flag.state.is_home())
+                .map(|flag| (flag.state.team, flag.home_bounds()))
+                .collect();
+            let mut captures = vec![];
+
+            for flag in self.flags.values_mut() {
+                if let Some(name) = flag.state.carrier.clone() {
+                    let carrier = player_controller
+                        .players
+                        .get_mut(&name)
+                        .filter(|player| player.state.lives > 0);
+                    match carrier {
+                        Some(carrier) => {
+                            flag.state.x = carrier.state.x;
+                            flag.state.y = carrier.state.y - flag::HEIGHT;
+
+                            let base = carrier.state.team.and_then(|team| bases.get(&team));
+                            if base.is_some_and(|base| collision::collides(carrier, base)) {
+                                captures.extend(carrier.state.team);
+                                carrier.state.carrying_flag = false;
+                                carrier.dirty = true;
+                                flag.return_home();
+                            }
+                        }
+                        None => {
+                            // the carrier died or left, drop the flag where it is
+                            if let Some(player) = player_controller.players.get_mut(&name) {
+                                player.state.carrying_flag = false;
+                                player.dirty = true;
+                            }
+                            flag.state.carrier = None;
+                            flag.state.return_timer = RETURN_TIME;
+                            flag.dirty = true;
+                        }
+                    }
+                    continue;
+                }
+
+                if !flag.state.is_home() {
+                    flag.state.return_timer -= tick.dt;
+                    if flag.state.return_timer <= 0.0 {
+                        flag.return_home();
+                    }
+                }
+
+                let player = player_controller
+                    .players
+                    .values_mut()
+                    .filter(|player| player.state.lives > 0 && player.state.team.is_some())
+                    .find(|player| collision::collides(*player, flag));
+                if let Some(player) = player {
+                    if player.state.team == Some(flag.state.team) {
+                        // touching a dropped own flag brings it back
+                        if !flag.state.is_home() {
+                            flag.return_home();
+                        }
+                    } else if !player.state.carrying_flag {
+                        flag.state.carrier = Some(player.state.name.clone());
+                        flag.dirty = true;
+                        player.state.carrying_flag = true;
+                        player.dirty = true;
+                    }
+                }
+            }
+
+            for team in captures {
+                game_controller.state.scores[team as usize] += 1;
+                game_controller.dirty = true;
+            }
+        }
+    }
+
+    /// Puts the flags of both teams on the floor cells furthest left and right.
+    pub fn reset(&mut self, map: &Map) {
+        let floor: Vec<_> = map.floor_cells().collect();
+        let leftmost = floor
+            .iter()
+            .min_by(|a, b| (a.x, -a.y).partial_cmp(&(b.x, -b.y)).unwrap());
+        let rightmost = floor
+            .iter()
+            .max_by(|a, b| (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap());
+
+        for (team, cell) in leftmost.into_iter().chain(rightmost).enumerate() {
+            // stand on the floor in the middle of the cell
+            let x = cell.x + (cell.w - flag::WIDTH) / 2.0;
+            let y = cell.y + cell.h - flag::HEIGHT;
+            self.flags.insert(team as u8, Flag::new(team as u8, x, y));
+        }
+    }
+}
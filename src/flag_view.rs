@@ -0,0 +1,65 @@
+use crate::flag;
+use crate::{FlagController, GameController, PlayerController};
+use graphics::{Context, Graphics};
+
+#[derive(Default)]
+pub struct FlagView {}
+
+impl FlagView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn draw<G: Graphics>(
+        &self,
+        controller: &FlagController,
+        game_controller: &GameController,
+        player_controller: &PlayerController,
+        c: &Context,
+        g: &mut G,
+    ) {
+        use graphics::*;
+
+        for flag in controller.flags.values() {
+            let mut color = game_controller.state.team_color(flag.state.team);
+
+            // carried flags follow the carrier, only changes are replicated
+            let carrier = flag
+                .state
+                .carrier
+                .as_ref()
+                .and_then(|name| player_controller.players.get(name));
+            let (x, y) = match carrier {
+                Some(carrier) => (carrier.state.x, carrier.state.y - flag::HEIGHT),
+                None => (flag.state.x, flag.state.y),
+            };
+
+            Rectangle::new([0.9, 0.9, 0.9, 1.0]).draw(
+                [x, y, 3.0, flag::HEIGHT],
+                &c.draw_state,
+                c.transform,
+                g,
+            );
+            Polygon::new(color).draw(
+                &[
+                    [x + 3.0, y],
+                    [x + flag::WIDTH, y + 6.0],
+                    [x + 3.0, y + 12.0],
+                ],
+                &c.draw_state,
+                c.transform,
+                g,
+            );
+
+            // mark the base
+            color[3] = 0.5;
+            let base = [
+                flag.state.home_x - 5.0,
+                flag.state.home_y + flag::HEIGHT - 4.0,
+                flag::WIDTH + 10.0,
+                4.0,
+            ];
+            Rectangle::new(color).draw(base, &c.draw_state, c.transform, g);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use crate::color;
+use crate::player;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// How shots treat teammates of the shooter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FriendlyFire {
+    /// Teammates are hurt like opponents.
+    On,
+    /// Shots pass through teammates.
+    #[default]
+    Off,
+    /// Shots hitting a teammate hurt the shooter instead.
+    Reflect,
+}
+
+impl FromStr for FriendlyFire {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "on" => Ok(FriendlyFire::On),
+            "off" => Ok(FriendlyFire::Off),
+            "reflect" => Ok(FriendlyFire::Reflect),
+            _ => Err(anyhow!(
+                "friendly fire must be on, off or reflect, got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mode {
+    /// The last player or team alive wins the round.
+    #[default]
+    LastManStanding,
+    /// Kills score until the time runs out.
+    Deathmatch,
+    /// Standing alone in the zone scores, the first to hold it long enough
+    /// wins the round.
+    KingOfTheHill,
+    /// Two teams score by bringing the enemy flag to their own base.
+    CaptureTheFlag,
+}
+
+impl FromStr for Mode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lms" => Ok(Mode::LastManStanding),
+            "deathmatch" => Ok(Mode::Deathmatch),
+            "koth" => Ok(Mode::KingOfTheHill),
+            "ctf" => Ok(Mode::CaptureTheFlag),
+            _ => Err(anyhow!(
+                "mode must be lms, deathmatch, koth or ctf, got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+/// Who points are credited to, a team or a single player in free-for-all.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    Team(u8),
+    Player(String),
+}
+
+/// Who takes the damage of a shot hitting a player.
+#[derive(Debug, PartialEq)]
+pub enum Hit {
+    Target,
+    Shooter,
+    Nobody,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    pub mode: Mode,
+    /// Number of teams, zero for free-for-all.
+    pub teams: u8,
+    pub friendly_fire: FriendlyFire,
+    /// Whether eliminated players come back after a delay instead of
+    /// waiting for the next round.
+    pub respawn: bool,
+    /// Points per team.
+    pub scores: Vec<u32>,
+    /// Points per player in free-for-all.
+    pub player_scores: HashMap<String, u32>,
+    /// Length of a round in seconds, zero if rounds are not timed.
+    pub time_limit: f64,
+    pub time_left: f64,
+    /// Area to hold in king of the hill.
+    pub zone: Option<[f64; 4]>,
+    /// Number of rounds started, lets clients notice when a round ends.
+    pub round: u32,
+    /// Set when something clients display changed, cleared by the controller.
+    #[serde(skip)]
+    pub changed: bool,
+}
+
+impl State {
+    /// Capture the flag is always played with two teams. Deathmatch always
+    /// respawns players, last man standing never does.
+    pub fn new(
+        mode: Mode,
+        teams: u8,
+        friendly_fire: FriendlyFire,
+        respawn: bool,
+        time_limit: f64,
+    ) -> Self {
+        let teams = match mode {
+            Mode::CaptureTheFlag => 2,
+            _ => teams,
+        };
+        let respawn = match mode {
+            Mode::Deathmatch => true,
+            Mode::LastManStanding => false,
+            _ => respawn,
+        };
+        Self {
+            mode,
+            teams,
+            friendly_fire,
+            respawn,
+            scores: vec![0; teams as usize],
+            time_limit,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_team_game(&self) -> bool {
+        self.teams > 0
+    }
+
+    pub fn team_color(&self, team: u8) -> [f32; 4] {
+        color::palette(self.teams as usize)[team as usize]
+    }
+
+    pub fn side(&self, player: &player::State) -> Side {
+        match player.team {
+            Some(team) if self.is_team_game() => Side::Team(team),
+            _ => Side::Player(player.name.clone()),
+        }
+    }
+
+    pub fn credit(&mut self, side: &Side, points: u32) {
+        match side {
+            Side::Team(team) => self.scores[*team as usize] += points,
+            Side::Player(name) => *self.player_scores.entry(name.clone()).or_insert(0) += points,
+        }
+        self.changed = true;
+    }
+
+    /// Decides who a shot of `shooter` hurts when it hits `target`.
+    pub fn resolve_hit(
+        &self,
+        shooter: &str,
+        shooter_team: Option<u8>,
+        target: &player::State,
+    ) -> Hit {
+        let teammate = self.is_team_game()
+            && shooter != target.name
+            && shooter_team.is_some()
+            && shooter_team == target.team;
+        if !teammate {
+            return Hit::Target;
+        }
+
+        match self.friendly_fire {
+            FriendlyFire::On => Hit::Target,
+            FriendlyFire::Off => Hit::Nobody,
+            FriendlyFire::Reflect => Hit::Shooter,
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use crate::color;
+use crate::game;
+use crate::game_mode;
+use crate::game_mode::GameMode;
+use crate::player::{Ammo, Player};
+use crate::player_controller::MAX_PLAYERS;
+use crate::rules::Rules;
+use crate::{Map, PlayerController};
+
+/// Teams and scores of the running game, decided by the host.
+pub struct GameController {
+    pub state: game::State,
+    pub rules: Rules,
+    pub dirty: bool,
+    mode: Box<dyn GameMode>,
+}
+
+impl GameController {
+    pub fn new(state: game::State, rules: Rules) -> Self {
+        Self {
+            mode: game_mode::from_mode(state.mode),
+            state,
+            rules,
+            dirty: true,
+        }
+    }
+
+    /// Puts a joining player into the preferred team, or the smallest one if
+    /// there is no valid preference or the preferred team is full. Returns
+    /// `None` in free-for-all.
+    pub fn assign_team(
+        &self,
+        preferred: Option<u8>,
+        player_controller: &PlayerController,
+    ) -> Option<u8> {
+        if !self.state.is_team_game() {
+            return None;
+        }
+
+        let is_full = |team| Self::members(team, player_controller) >= color::SHADES;
+        if let Some(team) = preferred.filter(|&team| team < self.state.teams && !is_full(team)) {
+            return Some(team);
+        }
+
+        // full teams only if all are, member_color then refuses the player
+        (0..self.state.teams)
+            .min_by_key(|&team| (is_full(team), Self::members(team, player_controller)))
+    }
+
+    /// Color for the next member of `team`, the first shade of the team color
+    /// no member uses yet. Returns `None` if the game or the team is full.
+    pub fn member_color(&self, team: u8, player_controller: &PlayerController) -> Option<[f32; 4]> {
+        if player_controller.players.len() >= MAX_PLAYERS {
+            return None;
+        }
+
+        let team_color = self.state.team_color(team);
+        (0..color::SHADES)
+            .map(|step| color::shade(team_color, step))
+            .find(|shade| {
+                player_controller
+                    .players
+                    .values()
+                    .filter(|player| player.state.team == Some(team))
+                    .all(|player| player.state.color != *shade)
+            })
+    }
+
+    /// Prepares scoring of a new round on the given map.
+    pub fn start_round(&mut self, map: &Map) {
+        self.state.round += 1;
+        self.state.changed = true;
+        self.mode.start_round(&mut self.state, map);
+        self.take_changes();
+    }
+
+    /// Lets the game mode score, returns whether the round is over.
+    pub fn update(&mut self, player_controller: &PlayerController, dt: f64) -> bool {
+        let round_over = self.mode.update(&mut self.state, player_controller, dt);
+        self.take_changes();
+        round_over
+    }
+
+    /// Brings eliminated players back after the respawn delay, standing on
+    /// the floor furthest away from their opponents.
+    pub fn respawn(&self, player_controller: &mut PlayerController, map: &Map, dt: f64) {
+        if !self.state.respawn {
+            return;
+        }
+
+        let mut ready = vec![];
+        for player in player_controller.players.values_mut() {
+            if player.state.lives > 0 {
+                player.respawn_in = None;
+                continue;
+            }
+
+            let respawn_in = player.respawn_in.unwrap_or(self.rules.respawn_delay) - dt;
+            player.respawn_in = Some(respawn_in);
+            if respawn_in <= 0.0 {
+                ready.push(player.state.name.clone());
+            }
+        }
+
+        for name in ready {
+            let side = self.state.side(&player_controller.players[&name].state);
+            let opponents: Vec<_> = player_controller
+                .players
+                .values()
+                .filter(|other| other.state.lives > 0 && self.state.side(&other.state) != side)
+                .map(|other| (other.state.x, other.state.y))
+                .collect();
+            let distance = |x: f64, y: f64| {
+                opponents
+                    .iter()
+                    .map(|&(ox, oy)| (ox - x).hypot(oy - y))
+                    .fold(f64::INFINITY, f64::min)
+            };
+            let spawn = map
+                .floor_cells()
+                .max_by(|a, b| distance(a.x, a.y).partial_cmp(&distance(b.x, b.y)).unwrap());
+
+            if let (Some(cell), Some(player)) = (spawn, player_controller.players.get_mut(&name)) {
+                Self::revive(player, &self.rules);
+                player.state.x = cell.x + (cell.w - player.state.width) / 2.0;
+                player.state.y = cell.y + cell.h - player.state.height;
+            }
+        }
+    }
+
+    fn revive(player: &mut Player, rules: &Rules) {
+        let state = &mut player.state;
+        state.lives = rules.lives;
+        state.dx = 0.0;
+        state.dy = 0.0;
+        state.effects = Default::default();
+        state.dash = 0.0;
+        state.invulnerable = rules.spawn_invulnerability;
+        state.carrying_flag = false;
+        player.ammo = Ammo::new(player.inputs.weapon);
+        player.last_attacker = None;
+        player.respawn_in = None;
+        player.dirty = true;
+        player.ammo_dirty = true;
+    }
+
+    fn take_changes(&mut self) {
+        if self.state.changed {
+            self.state.changed = false;
+            self.dirty = true;
+        }
+    }
+
+    fn members(team: u8, player_controller: &PlayerController) -> usize {
+        player_controller
+            .players
+            .values()
+            .filter(|player| player.state.team == Some(team))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{FriendlyFire, Mode};
+    use crate::Player;
+
+    #[test]
+    fn team_members_get_distinct_shades_until_the_team_is_full() {
+        let state = game::State::new(Mode::Deathmatch, 2, FriendlyFire::default(), true, 0.0);
+        let game_controller = GameController::new(state, Rules::default());
+        let mut player_controller = PlayerController::new();
+
+        for i in 0..color::SHADES {
+            let color = game_controller.member_color(0, &player_controller).unwrap();
+            let mut player = Player::new(i.to_string(), 0.0, 0.0, color, 1);
+            player.state.team = Some(0);
+            player_controller.players.insert(i.to_string(), player);
+        }
+        assert_eq!(game_controller.member_color(0, &player_controller), None);
+        assert!(game_controller
+            .member_color(1, &player_controller)
+            .is_some());
+
+        // a leaving member frees their shade
+        let freed = player_controller.players.remove("1").unwrap().state.color;
+        assert_eq!(
+            game_controller.member_color(0, &player_controller),
+            Some(freed)
+        );
+    }
+
+    #[test]
+    fn players_preferring_a_full_team_join_another() {
+        let state = game::State::new(Mode::Deathmatch, 3, FriendlyFire::default(), true, 0.0);
+        let game_controller = GameController::new(state, Rules::default());
+        let mut player_controller = PlayerController::new();
+
+        for i in 0..color::SHADES + 1 {
+            let team = game_controller.assign_team(Some(1), &player_controller);
+            let color = game_controller
+                .member_color(team.unwrap(), &player_controller)
+                .unwrap();
+            let mut player = Player::new(i.to_string(), 0.0, 0.0, color, 1);
+            player.state.team = team;
+            player_controller.players.insert(i.to_string(), player);
+        }
+        // the extra player went to the first of the smallest teams
+        assert_eq!(
+            GameController::members(1, &player_controller),
+            color::SHADES
+        );
+        assert_eq!(GameController::members(0, &player_controller), 1);
+        assert_eq!(
+            game_controller.assign_team(Some(1), &player_controller),
+            Some(2)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::collision;
+use crate::game;
+use crate::game::{Mode, Side};
+use crate::{Map, PlayerController};
+use std::collections::{HashMap, HashSet};
+
+/// Time a side has to hold the zone alone to win a king of the hill round.
+const HILL_TIME: f64 = 30.0;
+
+/// Rules for scoring and ending rounds, only run on the host.
+pub trait GameMode {
+    /// Prepares a new round on the given map.
+    fn start_round(&mut self, game: &mut game::State, map: &Map);
+
+    /// Scores the current tick, returns whether the round is over.
+    fn update(
+        &mut self,
+        game: &mut game::State,
+        player_controller: &PlayerController,
+        dt: f64,
+    ) -> bool;
+}
+
+pub fn from_mode(mode: Mode) -> Box<dyn GameMode> {
+    match mode {
+        Mode::LastManStanding => Box::new(LastManStanding),
+        Mode::Deathmatch => Box::new(Deathmatch::default()),
+        Mode::KingOfTheHill => Box::new(KingOfTheHill::default()),
+        Mode::CaptureTheFlag => Box::new(CaptureTheFlag),
+    }
+}
+
+/// Sides with players in the game and sides with players alive.
+fn sides(game: &game::State, player_controller: &PlayerController) -> (usize, HashSet<Side>) {
+    let players = player_controller.players.values();
+    let all: HashSet<_> = players.clone().map(|p| game.side(&p.state)).collect();
+    let alive = players
+        .filter(|player| player.state.lives > 0)
+        .map(|player| game.side(&player.state))
+        .collect();
+    (all.len(), alive)
+}
+
+/// Whether at most one side is left standing in a game of several. Never
+/// the case when players respawn.
+fn eliminated(game: &game::State, player_controller: &PlayerController) -> bool {
+    if game.respawn {
+        return false;
+    }
+
+    let (count, alive) = sides(game, player_controller);
+    count > 1 && alive.len() <= 1
+}
+
+/// The round ends when at most one player or team is alive, which wins it.
+pub struct LastManStanding;
+
+impl GameMode for LastManStanding {
+    fn start_round(&mut self, _game: &mut game::State, _map: &Map) {}
+
+    fn update(
+        &mut self,
+        game: &mut game::State,
+        player_controller: &PlayerController,
+        _dt: f64,
+    ) -> bool {
+        let (count, alive) = sides(game, player_controller);
+        if count <= 1 || alive.len() > 1 {
+            return false;
+        }
+
+        if let Some(winner) = alive.into_iter().next() {
+            game.credit(&winner, 1);
+        }
+        true
+    }
+}
+
+/// Every kill scores, the round ends when the time is up.
+#[derive(Default)]
+pub struct Deathmatch {
+    alive: HashSet<String>,
+}
+
+impl GameMode for Deathmatch {
+    fn start_round(&mut self, game: &mut game::State, _map: &Map) {
+        game.time_left = game.time_limit;
+        game.changed = true;
+        self.alive.clear();
+    }
+
+    fn update(
+        &mut self,
+        game: &mut game::State,
+        player_controller: &PlayerController,
+        dt: f64,
+    ) -> bool {
+        for player in player_controller.players.values() {
+            let state = &player.state;
+            let died = state.lives == 0 && self.alive.remove(&state.name);
+            if state.lives > 0 {
+                self.alive.insert(state.name.clone());
+            }
+
+            let killer = player
+                .last_attacker
+                .as_ref()
+                .and_then(|name| player_controller.players.get(name))
+                .filter(|killer| killer.state.name != state.name)
+                .map(|killer| game.side(&killer.state))
+                .filter(|side| *side != game.side(state));
+            if let Some(killer) = killer.filter(|_| died) {
+                game.credit(&killer, 1);
+            }
+        }
+
+        // only send whole seconds, clients don't need more precision
+        let before = game.time_left.ceil();
+        game.time_left = (game.time_left - dt).max(0.0);
+        if game.time_left.ceil() != before {
+            game.changed = true;
+        }
+
+        game.time_left <= 0.0 || eliminated(game, player_controller)
+    }
+}
+
+/// Standing in the zone while no other side does scores a point per second.
+#[derive(Default)]
+pub struct KingOfTheHill {
+    held: HashMap<Side, f64>,
+}
+
+impl GameMode for KingOfTheHill {
+    fn start_round(&mut self, game: &mut game::State, map: &Map) {
+        self.held.clear();
+
+        // the floor cell closest to the center of the map
+        let [width, height] = map.world_size();
+        let distance = |x: f64, y: f64| (x - width / 2.0).hypot(y - height / 2.0);
+        let center = map
+            .floor_cells()
+            .min_by(|a, b| distance(a.x, a.y).partial_cmp(&distance(b.x, b.y)).unwrap());
+
+        // three cells wide and two high, standing on the floor
+        game.zone =
+            center.map(|cell| [cell.x - cell.w, cell.y - cell.h, cell.w * 3.0, cell.h * 2.0]);
+        game.changed = true;
+    }
+
+    fn update(
+        &mut self,
+        game: &mut game::State,
+        player_controller: &PlayerController,
+        dt: f64,
+    ) -> bool {
+        let zone = match game.zone {
+            Some(zone) => zone,
+            None => return eliminated(game, player_controller),
+        };
+
+        let inside: HashSet<_> = player_controller
+            .players
+            .values()
+            .filter(|player| player.state.lives > 0 && collision::collides(*player, &zone))
+            .map(|player| game.side(&player.state))
+            .collect();
+
+        if inside.len() == 1 {
+            let side = inside.into_iter().next().unwrap();
+            let held = self.held.entry(side.clone()).or_insert(0.0);
+            let before = held.floor();
+            *held += dt;
+            if held.floor() > before {
+                game.credit(&side, 1);
+            }
+            if *held >= HILL_TIME {
+                return true;
+            }
+        }
+
+        eliminated(game, player_controller)
+    }
+}
+
+/// Flags score, see `FlagController`. A round only ends when a team is
+/// eliminated, which doesn't score.
+pub struct CaptureTheFlag;
+
+impl GameMode for CaptureTheFlag {
+    fn start_round(&mut self, _game: &mut game::State, _map: &Map) {}
+
+    fn update(
+        &mut self,
+        game: &mut game::State,
+        player_controller: &PlayerController,
+        _dt: f64,
+    ) -> bool {
+        eliminated(game, player_controller)
+    }
+}
@@ -0,0 +1,26 @@
+use crate::GameController;
+use graphics::{Context, Graphics};
+
+/// Draws mode-specific parts of the arena, like the king of the hill zone.
+#[derive(Default)]
+pub struct GameView {}
+
+impl GameView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn draw<G: Graphics>(&self, controller: &GameController, c: &Context, g: &mut G) {
+        use graphics::*;
+
+        if let Some(zone) = controller.state.zone {
+            Rectangle::new([1.0, 0.85, 0.2, 0.15]).draw(zone, &c.draw_state, c.transform, g);
+            Rectangle::new_border([1.0, 0.85, 0.2, 0.8], 2.0).draw(
+                zone,
+                &c.draw_state,
+                c.transform,
+                g,
+            );
+        }
+    }
+}
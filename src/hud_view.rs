@@ -0,0 +1,116 @@
+use crate::player_controller::DASH_COOLDOWN;
+use crate::{GameController, PlayerController};
+use graphics::{Context, Graphics};
+
+#[derive(Default)]
+pub struct HudView {}
+
+impl HudView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn draw<G: Graphics>(
+        &self,
+        game_controller: &GameController,
+        controller: &PlayerController,
+        local_player: Option<&str>,
+        size: [f64; 2],
+        c: &Context,
+        g: &mut G,
+    ) {
+        use graphics::*;
+
+        let border = Rectangle::new_border([0.0, 0.0, 0.0, 1.0], 1.0);
+        let [width, height] = size;
+
+        // scores in the top border wall, one pip per point
+        let game = &game_controller.state;
+        let scores: Vec<_> = if game.is_team_game() {
+            game.scores
+                .iter()
+                .enumerate()
+                .map(|(team, &score)| (game.team_color(team as u8), score))
+                .collect()
+        } else {
+            let mut scores: Vec<_> = game
+                .player_scores
+                .iter()
+                .filter_map(|(name, &score)| {
+                    let player = controller.players.get(name)?;
+                    Some((player.state.color, score))
+                })
+                .collect();
+            scores.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+            scores
+        };
+        let mut x = 10.0;
+        for (color, score) in scores {
+            let coords = [x, 8.0, 24.0, 24.0];
+            Rectangle::new(color).draw(coords, &c.draw_state, c.transform, g);
+            border.draw(coords, &c.draw_state, c.transform, g);
+            x += 32.0;
+            for _ in 0..score.min(20) {
+                let coords = [x, 14.0, 8.0, 12.0];
+                Rectangle::new(color).draw(coords, &c.draw_state, c.transform, g);
+                x += 12.0;
+            }
+            x += 24.0;
+        }
+
+        // remaining time of timed rounds in the top right corner
+        if game.time_limit > 0.0 && game.time_left > 0.0 {
+            let coords = [width - 110.0, 14.0, 100.0, 12.0];
+            let left = game.time_left / game.time_limit;
+            Rectangle::new([1.0; 4]).draw(
+                [coords[0], coords[1], coords[2] * left, coords[3]],
+                &c.draw_state,
+                c.transform,
+                g,
+            );
+            border.draw(coords, &c.draw_state, c.transform, g);
+        }
+
+        let player = match local_player.and_then(|name| controller.players.get(name)) {
+            Some(player) => player,
+            None => return,
+        };
+        if player.state.lives == 0 {
+            return;
+        }
+
+        let ammo = &player.ammo;
+        let color = player.state.color;
+
+        // ammo pips in the bottom border wall
+        for i in 0..ammo.weapon.magazine() {
+            let coords = [10.0 + f64::from(i) * 16.0, height - 32.0, 12.0, 24.0];
+            if i < ammo.loaded {
+                Rectangle::new(color).draw(coords, &c.draw_state, c.transform, g);
+            }
+            border.draw(coords, &c.draw_state, c.transform, g);
+        }
+
+        // dash readiness in the bottom right corner
+        let coords = [width - 110.0, height - 26.0, 100.0, 12.0];
+        let charge = 1.0 - player.state.dash_cooldown / DASH_COOLDOWN;
+        let mut dash_color = color;
+        if charge < 1.0 {
+            dash_color[3] = 0.4;
+        }
+        Rectangle::new(dash_color).draw(
+            [coords[0], coords[1], coords[2] * charge, coords[3]],
+            &c.draw_state,
+            c.transform,
+            g,
+        );
+        border.draw(coords, &c.draw_state, c.transform, g);
+
+        if ammo.reload > 0.0 {
+            let progress = 1.0 - ammo.reload / ammo.weapon.reload_time();
+            let bar = f64::from(ammo.weapon.magazine()) * 16.0 - 4.0;
+            let coords = [10.0, height - 6.0, bar * progress.max(0.0), 4.0];
+            Rectangle::new(color).draw(coords, &c.draw_state, c.transform, g);
+        }
+    }
+}
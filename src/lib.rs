@@ -5,38 +5,83 @@ extern crate opengl_graphics;
 extern crate piston;
 extern crate rand;
 
+mod audio;
+mod audio_controller;
+mod bot_controller;
+mod camera;
+mod camera_controller;
 mod cell;
 mod collision;
+mod color;
+mod effects_controller;
+mod effects_view;
 mod entity;
+mod flag;
+mod flag_controller;
+mod flag_view;
+mod game;
+mod game_controller;
+mod game_mode;
+mod game_view;
+mod hud_view;
 mod local_input_controller;
 mod map;
 mod map_controller;
 mod map_generator;
 mod map_view;
+mod minimap_view;
 mod network;
+mod particle;
+mod pathfinder;
+mod pickup;
+mod pickup_controller;
+mod pickup_view;
+mod platform;
+mod platform_controller;
 mod player;
 mod player_controller;
 mod player_view;
+mod portal;
+mod rules;
 mod shot;
 mod shot_controller;
 mod shot_view;
+mod spatial_hash;
+mod tile;
+mod weapon;
 
+use audio::NullBackend;
+use audio_controller::{AudioController, AudioSettings};
+use bot_controller::{BotController, Skill};
+use camera_controller::CameraController;
 use clap::ArgMatches;
+pub use effects_controller::{EffectsController, EffectsSettings};
+pub use effects_view::EffectsView;
+pub use flag_controller::FlagController;
+pub use flag_view::FlagView;
+pub use game_controller::GameController;
+pub use game_view::GameView;
 use glfw_window::GlfwWindow;
+use hud_view::HudView;
 use local_input_controller::LocalInputController;
 pub use map::Map;
 pub use map_controller::MapController;
 pub use map_view::{MapView, MapViewSettings};
+use minimap_view::MinimapView;
 use network::ClientController;
 use network::HostController;
 use opengl_graphics::{GlGraphics, OpenGL};
+pub use pickup_controller::PickupController;
+pub use pickup_view::PickupView;
 use piston::event_loop::{EventSettings, Events};
 use piston::input::*;
 use piston::window::WindowSettings;
 use piston::window::{Size, Window};
+pub use platform_controller::PlatformController;
 pub use player::Player;
 pub use player_controller::PlayerController;
 pub use player_view::PlayerView;
+use rules::Rules;
 pub use shot::Shot;
 pub use shot_controller::ShotController;
 pub use shot_view::ShotView;
@@ -45,6 +90,17 @@ pub fn run(matches: &ArgMatches) -> Result<(), anyhow::Error> {
     let host = matches.is_present("host");
     let join_server = matches.value_of("join");
     let observe = matches.is_present("observe");
+    let color = matches
+        .value_of("color")
+        .map(color::parse_hex)
+        .transpose()?;
+    let team = matches
+        .value_of("team")
+        .map(|team| match team.parse::<u8>()? {
+            0 => Err(anyhow::anyhow!("teams start at 1, got 0")),
+            team => Ok(team - 1),
+        })
+        .transpose()?;
 
     let mut window: GlfwWindow = WindowSettings::new("2dbattle", (1920, 1080))
         .exit_on_esc(true)
@@ -56,10 +112,48 @@ pub fn run(matches: &ArgMatches) -> Result<(), anyhow::Error> {
     let mut events = Events::new(EventSettings::new());
     let mut gl = GlGraphics::new(OpenGL::V3_3);
 
-    let map = Map::new();
-    let mut map_controller = MapController::new(map);
+    let template = matches.value_of("map").map(Map::load).transpose()?;
+    let size = matches.value_of("size").unwrap();
+    let size = size
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        // the generator adds wall offsets and lengths in u8, which only fits
+        // up to 170 cells
+        .filter(|&(width, height)| (16..=170).contains(&width) && (12..=170).contains(&height))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "size must be WIDTHxHEIGHT between 16x12 and 170x170, got {:?}",
+                size
+            )
+        })?;
+    let mut map_controller = MapController::new(template, size);
     let map_view_settings = MapViewSettings::new();
     let map_view = MapView::new(map_view_settings);
+    let mut platform_controller = PlatformController::new();
+
+    let teams = matches.value_of("teams").map(str::parse).transpose()?;
+    let mode = matches.value_of("mode").unwrap_or("lms").parse()?;
+    let friendly_fire = matches.value_of("friendly-fire").unwrap().parse()?;
+    let time_limit = matches.value_of("time-limit").unwrap().parse()?;
+    let rules = matches
+        .value_of("rules")
+        .map(Rules::load)
+        .transpose()?
+        .unwrap_or_default();
+    let mut game_controller = GameController::new(
+        game::State::new(
+            mode,
+            teams.unwrap_or(0),
+            friendly_fire,
+            matches.is_present("respawn"),
+            time_limit,
+        ),
+        rules,
+    );
+    if host {
+        game_controller.start_round(&map_controller.map);
+    }
+    let game_view = GameView::new();
 
     let mut player_controller = PlayerController::new();
     let player_view = PlayerView::new();
@@ -69,17 +163,58 @@ pub fn run(matches: &ArgMatches) -> Result<(), anyhow::Error> {
     } else {
         let name = matches.value_of("name").unwrap();
         if host {
-            let color = player_controller
-                .get_free_color()
-                .expect("no colors available");
-            let player = Player::new(name.to_string(), 50.0, 50.0, color);
-            player_controller.players.insert(name.to_string(), player);
+            add_player(name, color, team, &game_controller, &mut player_controller)?;
         }
         Some(LocalInputController::new(name.to_string()))
     };
 
-    let mut shot_controller = ShotController::new();
+    let bots: u32 = matches.value_of("bots").unwrap_or("0").parse()?;
+    let skill = Skill::new(matches.value_of("bot-skill").unwrap().parse()?);
+    let mut bot_controller = BotController::new(skill);
+    for i in 1..=bots {
+        let name = format!("bot {}", i);
+        add_player(&name, None, None, &game_controller, &mut player_controller)?;
+        bot_controller.add(name);
+    }
+
+    let mut shot_controller = ShotController::new(host);
     let shot_view = ShotView::new();
+    let hud_view = HudView::new();
+    let minimap_view = MinimapView::new();
+    let mut camera_controller = CameraController::new();
+
+    let mut effects_settings = if matches.is_present("no-effects") {
+        EffectsSettings::none()
+    } else {
+        EffectsSettings::new()
+    };
+    if matches.is_present("no-screen-shake") {
+        effects_settings.screen_shake = false;
+    }
+    let mut effects_controller = EffectsController::new(effects_settings);
+    let effects_view = EffectsView::new();
+
+    let mut audio_settings = AudioSettings::new();
+    let volume: f32 = matches.value_of("volume").unwrap().parse()?;
+    if !volume.is_finite() {
+        return Err(anyhow::anyhow!(
+            "volume must be a number between 0 and 1, got {}",
+            volume
+        ));
+    }
+    audio_settings.volume = volume.clamp(0.0, 1.0);
+    let audio_backend = if audio_settings.volume > 0.0 {
+        audio::default_backend()
+    } else {
+        Box::new(NullBackend::default())
+    };
+    let mut audio_controller = AudioController::new(audio_settings, audio_backend);
+
+    let mut pickup_controller = PickupController::new(host);
+    let pickup_view = PickupView::new();
+
+    let mut flag_controller = FlagController::new(host);
+    let flag_view = FlagView::new();
 
     let mut host = if host {
         let port = matches.value_of("port").expect("port is required");
@@ -91,28 +226,64 @@ pub fn run(matches: &ArgMatches) -> Result<(), anyhow::Error> {
         let name = local_input_controller
             .as_ref()
             .map(|l| l.local_player.as_str());
-        ClientController::connect(addr.parse().unwrap(), "0.0.0.0:0".parse().unwrap(), name)
-            .unwrap()
+        ClientController::connect(
+            addr.parse().unwrap(),
+            "0.0.0.0:0".parse().unwrap(),
+            name,
+            color,
+            team,
+        )
+        .unwrap()
     });
 
-    fn get_scaling(window: &GlfwWindow) -> (f64, f64, f64) {
-        let Size { width, height } = window.size();
-        let scale = (width / 1920.0).min(height / 1080.0);
-        let translate_x = (width - 1920.0 * scale) / 2.0;
-        let translate_y = (height - 1080.0 * scale) / 2.0;
-        (scale, translate_x, translate_y)
+    /// Adds a player that is simulated on the host itself. Fails when the
+    /// game is full.
+    fn add_player(
+        name: &str,
+        color: Option<[f32; 4]>,
+        team: Option<u8>,
+        game_controller: &GameController,
+        player_controller: &mut PlayerController,
+    ) -> Result<(), anyhow::Error> {
+        let team = game_controller.assign_team(team, player_controller);
+        let color = match team {
+            Some(team) => game_controller.member_color(team, player_controller),
+            None => player_controller.get_free_color(color),
+        };
+        let color = color.ok_or_else(|| {
+            anyhow::anyhow!(
+                "no room for {:?}, at most {} players and {} per team can play",
+                name,
+                player_controller::MAX_PLAYERS,
+                color::SHADES
+            )
+        })?;
+        let mut player = Player::new(
+            name.to_string(),
+            50.0,
+            50.0,
+            color,
+            game_controller.rules.lives,
+        );
+        player.state.team = team;
+        player_controller.players.insert(name.to_string(), player);
+        Ok(())
     }
 
     while let Some(event) = events.next(&mut window) {
         if let Some(local_input_controller) = local_input_controller.as_mut() {
-            local_input_controller.event(&event, &mut player_controller, get_scaling(&window));
+            let scaling = camera_controller.camera.scaling();
+            local_input_controller.event(&event, &mut player_controller, scaling);
         }
         if let Some(client) = client.as_mut() {
             client.event(
                 &event,
+                &mut game_controller,
                 &mut player_controller,
                 &mut map_controller,
                 &mut shot_controller,
+                &mut pickup_controller,
+                &mut flag_controller,
                 &mut local_input_controller,
             )?;
         }
@@ -120,29 +291,114 @@ pub fn run(matches: &ArgMatches) -> Result<(), anyhow::Error> {
             map_controller.event(&event);
             host.event(
                 &event,
+                &mut game_controller,
                 &mut player_controller,
                 &mut shot_controller,
+                &mut pickup_controller,
+                &mut flag_controller,
                 &mut map_controller,
             );
         }
-        player_controller.event(&map_controller.map, &mut shot_controller, &event);
-        shot_controller.event(&map_controller.map, &mut player_controller, &event);
+        if host.is_some() {
+            bot_controller.event(
+                &map_controller.map,
+                &game_controller.state,
+                &game_controller.rules,
+                &flag_controller,
+                &mut player_controller,
+                &event,
+            );
+        }
+        platform_controller.event(&mut map_controller.map, &mut player_controller, &event);
+        player_controller.event(
+            &map_controller.map,
+            &game_controller.state,
+            &game_controller.rules,
+            &mut shot_controller,
+            &event,
+        );
+        shot_controller.event(
+            &mut map_controller.map,
+            &game_controller.state,
+            &game_controller.rules,
+            &mut player_controller,
+            &event,
+        );
+        pickup_controller.event(&map_controller.map, &mut player_controller, &event);
+        flag_controller.event(
+            &map_controller.map,
+            &mut game_controller,
+            &mut player_controller,
+            &event,
+        );
+
+        let local_player = local_input_controller
+            .as_ref()
+            .map(|l| l.local_player.as_str());
+        let Size { width, height } = window.size();
+        camera_controller.event(
+            &event,
+            [width, height],
+            &map_controller.map,
+            &player_controller,
+            local_player,
+        );
+
+        effects_controller.event(&event, &player_controller, &shot_controller, local_player);
+        audio_controller.event(
+            &event,
+            &game_controller,
+            &player_controller,
+            &shot_controller,
+            &camera_controller.camera,
+        );
 
         if let Some(r) = event.render_args() {
-            gl.draw(r.viewport(), |mut c, g| {
+            gl.draw(r.viewport(), |c, g| {
                 use graphics::{clear, Transformed};
                 clear([0.0, 0.0, 0.0, 1.0], g);
 
-                let (scale, translate_x, translate_y) = get_scaling(&window);
-                // scale to window size and center
-                c.transform = c
-                    .transform
-                    .trans(translate_x, translate_y)
+                let camera = &camera_controller.camera;
+                let (scale, translate_x, translate_y) = camera.scaling();
+                let [shake_x, shake_y] = effects_controller.shake_offset();
+                // overlays are scaled to the window but don't move or zoom
+                let overlay_scale = camera.base_scale();
+                let overlay = c.scale(overlay_scale, overlay_scale);
+                let overlay_size = [width / overlay_scale, height / overlay_scale];
+                // the part of the world seen by the camera
+                let c = c
+                    .trans(translate_x + shake_x, translate_y + shake_y)
                     .scale(scale, scale);
 
                 map_view.draw(&map_controller, &c, g);
-                player_view.draw(&player_controller, &c, g);
+                game_view.draw(&game_controller, &c, g);
+                pickup_view.draw(&pickup_controller, &c, g);
+                flag_view.draw(
+                    &flag_controller,
+                    &game_controller,
+                    &player_controller,
+                    &c,
+                    g,
+                );
+                player_view.draw(&player_controller, &game_controller.rules, &c, g);
                 shot_view.draw(&shot_controller, &c, g);
+                effects_view.draw(&effects_controller, &player_controller, &c, g);
+                hud_view.draw(
+                    &game_controller,
+                    &player_controller,
+                    local_player,
+                    overlay_size,
+                    &overlay,
+                    g,
+                );
+                minimap_view.draw(
+                    &map_controller,
+                    &player_controller,
+                    camera,
+                    overlay_size,
+                    &overlay,
+                    g,
+                );
             });
         }
     }
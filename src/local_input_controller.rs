@@ -1,10 +1,15 @@
 use crate::player_controller::PlayerController;
+use crate::weapon::Weapon;
 use piston::input::{Button, ButtonState, GenericEvent, Key, MouseButton};
 
 #[derive(Default)]
 pub struct LocalInputController {
     pub local_player: String,
     space: bool,
+    shift: bool,
+    /// Last mouse position in the window, kept to aim at the same spot
+    /// while the camera moves.
+    cursor: Option<[f64; 2]>,
 }
 
 impl LocalInputController {
@@ -41,6 +46,17 @@ impl LocalInputController {
 
                     self.space = input.state == ButtonState::Press;
                 }
+                Button::Keyboard(Key::LShift) => {
+                    if input.state != ButtonState::Press {
+                        player.dirty = true;
+                        player.inputs.dash = false;
+                    } else if !self.shift {
+                        player.dirty = true;
+                        player.inputs.dash = true;
+                    }
+
+                    self.shift = input.state == ButtonState::Press;
+                }
                 Button::Keyboard(Key::A) => {
                     let new_state = input.state == ButtonState::Press;
                     player.dirty = player.inputs.left != new_state;
@@ -51,6 +67,16 @@ impl LocalInputController {
                     player.dirty = player.inputs.right != new_state;
                     player.inputs.right = new_state;
                 }
+                Button::Keyboard(key @ Key::D1)
+                | Button::Keyboard(key @ Key::D2)
+                | Button::Keyboard(key @ Key::D3)
+                | Button::Keyboard(key @ Key::D4)
+                    if input.state == ButtonState::Press =>
+                {
+                    let weapon = Weapon::ALL[key as usize - Key::D1 as usize];
+                    player.dirty = player.inputs.weapon != weapon;
+                    player.inputs.weapon = weapon;
+                }
                 Button::Mouse(MouseButton::Left) => {
                     let new_state = input.state == ButtonState::Press;
                     player.dirty = player.inputs.shoot != new_state;
@@ -61,11 +87,19 @@ impl LocalInputController {
         }
 
         if let Some(mouse_pos) = e.mouse_cursor_args() {
-            player.inputs.mouse_x = (mouse_pos[0] - translate_x) / scale;
-            player.inputs.mouse_y = (mouse_pos[1] - translate_y) / scale;
-            if player.inputs.shoot {
+            self.cursor = Some(mouse_pos);
+        }
+        if let Some([cursor_x, cursor_y]) = self.cursor {
+            // the aim also moves when the camera scrolls under a still cursor
+            let mouse_x = (cursor_x - translate_x) / scale;
+            let mouse_y = (cursor_y - translate_y) / scale;
+            if player.inputs.shoot
+                && (mouse_x != player.inputs.mouse_x || mouse_y != player.inputs.mouse_y)
+            {
                 player.dirty = true;
             }
+            player.inputs.mouse_x = mouse_x;
+            player.inputs.mouse_y = mouse_y;
         }
     }
 }
@@ -1,62 +1,376 @@
 use crate::cell::Cell;
+use crate::entity::{Bounds, Speed};
 use crate::map_generator;
+use crate::platform;
+use crate::platform::Platform;
+use crate::portal;
+use crate::portal::{Facing, Transfer};
+use crate::tile::Tile;
+use anyhow::{anyhow, Context};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 use std::iter::Iterator;
+use std::path::Path;
+
+/// Side of a cell in world units, the world is as large as the grid.
+pub const CELL_SIZE: f64 = 40.0;
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Map {
     pub width: u8,
     pub height: u8,
-    pub cells: Vec<Vec<bool>>,
+    pub cells: Vec<Vec<Tile>>,
+    pub platforms: Vec<Platform>,
+    /// Grid positions of cells changed since the last broadcast.
+    #[serde(skip)]
+    pub changed_cells: Vec<(u8, u8)>,
 }
 
 impl Map {
-    pub fn new() -> Self {
-        let width = 48;
-        let height = 27;
+    /// Generates a random map with the given size in cells.
+    pub fn new(width: u8, height: u8) -> Self {
         let cells = map_generator::generate_map(width, height);
+        let platforms = map_generator::generate_platforms(&cells);
+
+        Self::from_grid(width, height, cells, &platforms)
+    }
 
-        Self {
+    fn from_grid(width: u8, height: u8, cells: Vec<Vec<Tile>>, paths: &[platform::Path]) -> Self {
+        let mut map = Self {
             width,
             height,
             cells,
+            platforms: vec![],
+            changed_cells: vec![],
+        };
+
+        let (cw, ch) = map.cell_size();
+        map.platforms = paths
+            .iter()
+            .enumerate()
+            .map(|(id, path)| {
+                let points = path
+                    .points
+                    .iter()
+                    .map(|&(x, y)| [f64::from(x) * cw, f64::from(y) * ch])
+                    .collect();
+                Platform::new(id as u32, f64::from(path.width) * cw, ch / 2.0, points)
+            })
+            .collect();
+        map
+    }
+
+    /// Reads a hand-made map, see [`Map::parse`] for the format.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("could not read map {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("invalid map {}", path.display()))
+    }
+
+    /// Parses a map from text with one line per row and one character per
+    /// cell, using the characters of [`Tile::from_char`]. Short lines are
+    /// padded with empty cells.
+    ///
+    /// Letters mark portals, the two cells with the same letter are linked.
+    /// Portals face their first empty neighbor, checking up, down, left and
+    /// right in that order.
+    ///
+    /// The grid may be followed by moving platforms in the form
+    /// `platform <width> <x>,<y> <x>,<y> ...`, listing the cells the
+    /// platform's top-left corner moves between.
+    pub fn parse(text: &str) -> Result<Self, anyhow::Error> {
+        let mut rows: Vec<Vec<char>> = vec![];
+        let mut paths = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if let Some(definition) = line.strip_prefix("platform ") {
+                let path = parse_platform(definition)
+                    .with_context(|| format!("invalid platform in line {}", i + 1))?;
+                paths.push(path);
+            } else {
+                rows.push(line.chars().collect());
+            }
+        }
+
+        let height = rows.len();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if width == 0 || height == 0 || width > 255 || height > 255 {
+            return Err(anyhow!(
+                "map must be between 1x1 and 255x255 cells, got {}x{}",
+                width,
+                height
+            ));
+        }
+
+        let mut cells = vec![vec![Tile::Empty; height]; width];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &c) in row.iter().enumerate() {
+                cells[x][y] = Tile::from_char(c)
+                    .ok_or_else(|| anyhow!("unknown tile {:?} in line {}", c, y + 1))?;
+            }
+        }
+
+        let mut pairs = HashMap::new();
+        for tile in cells.iter().flatten() {
+            if let Tile::Portal { pair, .. } = tile {
+                *pairs.entry(*pair).or_insert(0) += 1;
+            }
+        }
+        if let Some((pair, _)) = pairs.iter().find(|(_, &count)| count != 2) {
+            return Err(anyhow!(
+                "portal {} must appear exactly twice",
+                char::from(b'A' + pair)
+            ));
+        }
+
+        for path in &paths {
+            let inside = path
+                .points
+                .iter()
+                .all(|&(x, y)| x as usize + path.width as usize <= width && (y as usize) < height);
+            if !inside {
+                return Err(anyhow!("platform {:?} leaves the map", path.points));
+            }
         }
+
+        let mut map = Self::from_grid(width as u8, height as u8, cells, &paths);
+        map.orient_portals();
+        Ok(map)
+    }
+
+    fn orient_portals(&mut self) {
+        for gx in 0..self.width as usize {
+            for gy in 0..self.height as usize {
+                if let Tile::Portal { pair, .. } = self.cells[gx][gy] {
+                    let facing = Facing::ALL
+                        .iter()
+                        .copied()
+                        .find(|&facing| {
+                            self.neighbor(gx, gy, facing)
+                                .is_some_and(|(nx, ny)| self.cells[nx][ny].is_empty())
+                        })
+                        .unwrap_or(Facing::Up);
+                    self.cells[gx][gy] = Tile::Portal { pair, facing };
+                }
+            }
+        }
+    }
+
+    fn neighbor(&self, gx: usize, gy: usize, facing: Facing) -> Option<(usize, usize)> {
+        let (ox, oy) = facing.offset();
+        let nx = gx as i32 + ox;
+        let ny = gy as i32 + oy;
+        if nx < 0 || ny < 0 || nx >= i32::from(self.width) || ny >= i32::from(self.height) {
+            None
+        } else {
+            Some((nx as usize, ny as usize))
+        }
+    }
+
+    /// Teleports entities whose center entered a portal from its open side.
+    pub fn portal_transfer<E: Bounds + Speed>(&self, entity: &E) -> Option<Transfer> {
+        let center_x = entity.x() + entity.w() / 2.0;
+        let center_y = entity.y() + entity.h() / 2.0;
+        let (gx, gy) = self.coords_at(center_x, center_y);
+        let (pair, entry) = match self.cells.get(gx).and_then(|column| column.get(gy)) {
+            Some(&Tile::Portal { pair, facing }) => (pair, facing),
+            _ => return None,
+        };
+
+        let (fx, fy) = entry.offset();
+        if entity.dx() * f64::from(fx) + entity.dy() * f64::from(fy) >= 0.0 {
+            // moving away from the portal
+            return None;
+        }
+
+        let (exit_x, exit_y, exit) = (0..self.width as usize)
+            .cartesian_product(0..self.height as usize)
+            .filter(|&position| position != (gx, gy))
+            .find_map(|(x, y)| match self.cells[x][y] {
+                Tile::Portal {
+                    pair: other,
+                    facing,
+                } if other == pair => Some((x, y, facing)),
+                _ => None,
+            })?;
+        let (front_x, front_y) = self.neighbor(exit_x, exit_y, exit)?;
+        let front = self.cell_bounds(front_x, front_y);
+
+        let (dx, dy) = portal::rotate(entity.dx(), entity.dy(), entry, exit);
+        Some(Transfer {
+            x: front.x + (front.w - entity.w()) / 2.0,
+            y: front.y + (front.h - entity.h()) / 2.0,
+            dx,
+            dy,
+        })
+    }
+
+    /// Size of the map in world units.
+    pub fn world_size(&self) -> [f64; 2] {
+        let (cw, ch) = self.cell_size();
+        [f64::from(self.width) * cw, f64::from(self.height) * ch]
     }
 
-    fn coords_at(&self, x: f64, y: f64) -> (usize, usize) {
-        (
-            (x / 1920.0 * f64::from(self.width)) as usize,
-            (y / 1080.0 * f64::from(self.height)) as usize,
-        )
+    pub fn coords_at(&self, x: f64, y: f64) -> (usize, usize) {
+        let (cw, ch) = self.cell_size();
+        ((x / cw) as usize, (y / ch) as usize)
     }
 
     fn cell_at_grid(&self, gx: usize, gy: usize) -> Option<Cell> {
-        if self.cells[gx][gy] {
-            let cw = 1920.0 / f64::from(self.width);
-            let ch = 1080.0 / f64::from(self.height);
-            Some(Cell {
-                x: gx as f64 * cw,
-                y: gy as f64 * ch,
-                w: cw,
-                h: ch,
-                state: self.cells[gx][gy],
-            })
+        if !self.cells[gx][gy].is_empty() {
+            Some(self.cell_bounds(gx, gy))
         } else {
             None
         }
     }
 
+    fn cell_size(&self) -> (f64, f64) {
+        (CELL_SIZE, CELL_SIZE)
+    }
+
+    pub fn cell_bounds(&self, gx: usize, gy: usize) -> Cell {
+        let (cw, ch) = self.cell_size();
+        Cell {
+            x: gx as f64 * cw,
+            y: gy as f64 * ch,
+            w: cw,
+            h: ch,
+            tile: self.cells[gx][gy],
+        }
+    }
+
+    /// Tile at a position, `None` outside of the map.
+    pub fn tile_at(&self, x: f64, y: f64) -> Option<Tile> {
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let (gx, gy) = self.coords_at(x, y);
+        self.cells
+            .get(gx)
+            .and_then(|column| column.get(gy))
+            .copied()
+    }
+
     pub fn all_cells(&self) -> impl Iterator<Item = Cell> + '_ {
         (0..self.width as usize)
             .cartesian_product(0..self.height as usize)
             .filter_map(move |(gx, gy)| self.cell_at_grid(gx, gy))
     }
 
-    pub fn cells_around(&self, x: f64, y: f64) -> impl Iterator<Item = Cell> + '_ {
+    /// Empty cells directly above a wall, where players and items can stand.
+    pub fn floor_cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        (0..self.width as usize)
+            .cartesian_product(0..self.height as usize - 1)
+            .filter(move |&(gx, gy)| {
+                self.cells[gx][gy].is_empty() && self.cells[gx][gy + 1].is_floor()
+            })
+            .map(move |(gx, gy)| self.cell_bounds(gx, gy))
+    }
+
+    /// Damages the cell at the given position if it is destructible.
+    pub fn damage_at(&mut self, x: f64, y: f64, damage: u8) {
         let (gx, gy) = self.coords_at(x, y);
-        (gx.max(1) - 1..(gx + 2).min(self.width as usize))
-            .cartesian_product(gy.max(1) - 1..(gy + 2).min(self.height as usize))
+        if gx >= self.width as usize || gy >= self.height as usize {
+            return;
+        }
+
+        if let Some(tile) = self.cells[gx][gy].damaged(damage) {
+            self.cells[gx][gy] = tile;
+            self.changed_cells.push((gx as u8, gy as u8));
+        }
+    }
+
+    /// Non-empty cells overlapping the given bounds, found from the grid
+    /// instead of checking every cell.
+    pub fn cells_in<B: Bounds>(&self, bounds: &B) -> impl Iterator<Item = Cell> + '_ {
+        let (x0, y0) = self.coords_at(bounds.x().max(0.0), bounds.y().max(0.0));
+        let (right, bottom) = (bounds.x() + bounds.w(), bounds.y() + bounds.h());
+        let (x1, y1) = self.coords_at(right.max(0.0), bottom.max(0.0));
+        // bounds entirely left of or above the map overlap no cells
+        let x_end = if right < 0.0 { 0 } else { x1 + 1 };
+        let y_end = if bottom < 0.0 { 0 } else { y1 + 1 };
+        (x0..x_end.min(self.width as usize))
+            .cartesian_product(y0..y_end.min(self.height as usize))
             .filter_map(move |(gx, gy)| self.cell_at_grid(gx, gy))
     }
 }
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.height as usize {
+            let row: String = self
+                .cells
+                .iter()
+                .map(|column| column[y].to_char())
+                .collect();
+            writeln!(f, "{}", row)?;
+        }
+
+        let (cw, ch) = self.cell_size();
+        for platform in &self.platforms {
+            write!(f, "platform {}", (platform.w / cw).round())?;
+            for [x, y] in &platform.path {
+                write!(f, " {},{}", (x / cw).round(), (y / ch).round())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_platform(definition: &str) -> Result<platform::Path, anyhow::Error> {
+    let mut parts = definition.split_whitespace();
+    let width = parts
+        .next()
+        .ok_or_else(|| anyhow!("missing platform width"))?
+        .parse()?;
+    let points = parts
+        .map(|point| {
+            let (x, y) = point
+                .split_once(',')
+                .ok_or_else(|| anyhow!("expected x,y but got {:?}", point))?;
+            Ok((x.parse()?, y.parse()?))
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+    if width == 0 || !points.iter().any(|&point| point != points[0]) {
+        return Err(anyhow!(
+            "platform needs a width and at least two different points"
+        ));
+    }
+    Ok(platform::Path { width, points })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_in_clamps_bounds_outside_the_map() {
+        let map = Map::parse("###\n#.#\n###").unwrap();
+        let (cw, ch) = map.cell_size();
+        // bounds in cells
+        let all = |[x, y, w, h]: [f64; 4]| {
+            map.cells_in(&[x * cw, y * ch, w * cw, h * ch])
+                .map(|cell| ((cell.x / cw) as u32, (cell.y / ch) as u32))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(all([-10.0, -10.0, 50.0, 50.0]).len(), 8);
+        assert_eq!(all([-2.0, -2.0, 2.5, 2.5]), vec![(0, 0)]);
+        assert_eq!(all([2.5, 2.5, 10.0, 10.0]), vec![(2, 2)]);
+        assert!(all([-2.0, -2.0, 1.0, 1.0]).is_empty());
+        assert!(all([10.0, 10.0, 0.5, 0.5]).is_empty());
+    }
+
+    #[test]
+    fn platforms_need_two_different_points() {
+        assert!(Map::parse("...\nplatform 1 0,0 2,0").is_ok());
+        assert!(Map::parse("...\nplatform 1 0,0").is_err());
+        assert!(Map::parse("...\nplatform 1 0,0 0,0").is_err());
+        assert!(Map::parse("...\nplatform 0 0,0 2,0").is_err());
+    }
+}
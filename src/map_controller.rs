@@ -3,11 +3,29 @@ use piston::input::{Button, ButtonArgs, ButtonState, GenericEvent, Key};
 
 pub struct MapController {
     pub map: Map,
+    /// Hand-made map played every round instead of generated ones.
+    pub template: Option<Map>,
+    /// Size in cells of generated maps.
+    pub size: (u8, u8),
 }
 
 impl MapController {
-    pub fn new(map: Map) -> Self {
-        Self { map }
+    pub fn new(template: Option<Map>, size: (u8, u8)) -> Self {
+        let mut controller = Self {
+            map: Map::default(),
+            template,
+            size,
+        };
+        controller.reset();
+        controller
+    }
+
+    /// Replaces the map for a new round.
+    pub fn reset(&mut self) {
+        self.map = match &self.template {
+            Some(template) => template.clone(),
+            None => Map::new(self.size.0, self.size.1),
+        };
     }
 
     pub fn event<E: GenericEvent>(&mut self, e: &E) {
@@ -17,7 +35,7 @@ impl MapController {
             ..
         }) = e.button_args()
         {
-            self.map = Map::new();
+            self.reset();
         }
     }
 }
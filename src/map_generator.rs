@@ -1,9 +1,24 @@
+use crate::platform;
+use crate::portal::Facing;
+use crate::tile::{Tile, DESTRUCTIBLE_HP};
 use rand::distributions::{Distribution, Uniform};
+use rand::rngs::ThreadRng;
 use rand::Rng;
 use std::collections::HashSet;
 use std::ops::RangeInclusive;
 
-pub fn generate_map(width: u8, height: u8) -> Vec<Vec<bool>> {
+/// Cells a player can rise with a regular jump.
+pub const JUMP_HEIGHT: usize = 8;
+/// Cells a player can move sideways while jumping onto a ledge.
+pub const JUMP_DISTANCE: usize = 6;
+/// Cells a player can rise with a wall-jump, a bit less than physically
+/// possible to keep it comfortable.
+const WALL_JUMP_HEIGHT: usize = 5;
+
+/// Cells in front of two linked portals.
+type PortalLink = ((usize, usize), (usize, usize));
+
+pub fn generate_map(width: u8, height: u8) -> Vec<Vec<Tile>> {
     let mut rng = rand::thread_rng();
     let num_walls = 16;
     let mut walls: Vec<Wall> = vec![];
@@ -18,6 +33,15 @@ pub fn generate_map(width: u8, height: u8) -> Vec<Vec<bool>> {
     let height_distribution = Uniform::from(min_height..max_height);
 
     loop {
+        // half of the maps wrap around through a portal pair in the side walls
+        let portals: Vec<_> = if rng.gen::<bool>() {
+            let left = y_distribution.sample(&mut rng);
+            let right = y_distribution.sample(&mut rng);
+            vec![((1, left as usize), (width as usize - 2, right as usize))]
+        } else {
+            vec![]
+        };
+
         walls.push(Wall::new(0, 0, width, 1));
         walls.push(Wall::new(0, height - 1, width, 1));
         walls.push(Wall::new(0, 0, 1, height));
@@ -36,7 +60,8 @@ pub fn generate_map(width: u8, height: u8) -> Vec<Vec<bool>> {
             } else {
                 (1, height_distribution.sample(&mut rng))
             };
-            let wall = Wall::new(x, y, wall_width, wall_height);
+            let mut wall = Wall::new(x, y, wall_width, wall_height);
+            wall.tile = random_tile(wall.is_horizontal(), &mut rng);
 
             if x + wall.width > width || y + wall.height > height {
                 continue;
@@ -54,7 +79,7 @@ pub fn generate_map(width: u8, height: u8) -> Vec<Vec<bool>> {
 
             walls.push(wall);
 
-            if !valid_map(&to_grid(&walls, width, height)) {
+            if !valid_map(&to_grid(&walls, width, height), &portals) {
                 tries += 1;
                 walls.pop();
                 continue;
@@ -62,13 +87,101 @@ pub fn generate_map(width: u8, height: u8) -> Vec<Vec<bool>> {
         }
 
         if tries <= 500 {
-            break;
+            let mut tiles = to_tiles(&walls, width, height);
+            place_portals(&mut tiles, &portals);
+            break tiles;
         }
 
         walls.clear();
     }
+}
+
+/// Turns the border cells behind the given front cells into portals, as long
+/// as both fronts are open.
+fn place_portals(tiles: &mut [Vec<Tile>], portals: &[PortalLink]) {
+    for (pair, &((left_x, left_y), (right_x, right_y))) in portals.iter().enumerate() {
+        if tiles[left_x][left_y].is_empty() && tiles[right_x][right_y].is_empty() {
+            tiles[left_x - 1][left_y] = Tile::Portal {
+                pair: pair as u8,
+                facing: Facing::Right,
+            };
+            tiles[right_x + 1][right_y] = Tile::Portal {
+                pair: pair as u8,
+                facing: Facing::Left,
+            };
+        }
+    }
+}
 
-    to_grid(&walls, width, height)
+/// Picks the kind of a non-border wall. Platform-like tiles only make sense
+/// for horizontal walls.
+fn random_tile(horizontal: bool, rng: &mut ThreadRng) -> Tile {
+    let roll = rng.gen::<f64>();
+    match (horizontal, roll) {
+        (_, r) if r < 0.2 => Tile::Destructible {
+            hp: DESTRUCTIBLE_HP,
+        },
+        (true, r) if r < 0.3 => Tile::OneWay,
+        (true, r) if r < 0.38 => Tile::Ice,
+        (true, r) if r < 0.43 => Tile::Bounce,
+        (true, r) if r < 0.47 => Tile::Spikes,
+        (false, r) if r < 0.3 => Tile::Absorb,
+        _ => Tile::Solid,
+    }
+}
+
+fn to_tiles(walls: &[Wall], width: u8, height: u8) -> Vec<Vec<Tile>> {
+    let mut tiles = vec![vec![Tile::Empty; height as usize]; width as usize];
+
+    for wall in walls {
+        for x in wall.x..wall.x + wall.width {
+            for y in wall.y..wall.y + wall.height {
+                // earlier walls, like the border, win where walls cross
+                if tiles[x as usize][y as usize].is_empty() {
+                    tiles[x as usize][y as usize] = wall.tile;
+                }
+            }
+        }
+    }
+
+    tiles
+}
+
+/// Places up to two horizontally moving platforms in open areas.
+pub fn generate_platforms(tiles: &[Vec<Tile>]) -> Vec<platform::Path> {
+    let mut rng = rand::thread_rng();
+    let width = tiles.len();
+    let height = tiles[0].len();
+    let platform_width = 3;
+    let travel = 8;
+    let mut platforms: Vec<platform::Path> = vec![];
+
+    for _ in 0..200 {
+        if platforms.len() >= 2 {
+            break;
+        }
+
+        let x = rng.gen_range(1, width - platform_width - travel);
+        let y = rng.gen_range(4, height - 4);
+
+        // leave room to stand on it and to pass below
+        let free = (x - 1..x + platform_width + travel + 1)
+            .all(|tx| (y - 3..=y + 2).all(|ty| tiles[tx][ty].is_empty()));
+        let apart = platforms.iter().all(|other| {
+            let (other_x, other_y) = other.points[0];
+            (other_y as usize).abs_diff(y) > 4
+                || (other_x as usize).abs_diff(x) > platform_width + travel
+        });
+
+        if free && apart {
+            platforms.push(platform::Path {
+                width: platform_width as u8,
+                points: vec![(x as u8, y as u8), ((x + travel) as u8, y as u8)],
+            });
+        }
+    }
+
+    platforms
 }
 
 fn to_grid(walls: &[Wall], width: u8, height: u8) -> Vec<Vec<bool>> {
@@ -85,7 +198,9 @@ fn to_grid(walls: &[Wall], width: u8, height: u8) -> Vec<Vec<bool>> {
     grid
 }
 
-fn valid_map(grid: &[Vec<bool>]) -> bool {
+/// `portals` holds pairs of cells in front of linked portals, which count as
+/// connected for reachability.
+fn valid_map(grid: &[Vec<bool>], portals: &[PortalLink]) -> bool {
     let width = grid.len();
     let height = grid[0].len();
     let mut jump_test_left: Vec<(usize, usize)> = vec![];
@@ -135,13 +250,13 @@ fn valid_map(grid: &[Vec<bool>]) -> bool {
 
     // reachability through jumping
     for (x, y) in jump_test_left {
-        if !jumpable(x, y, grid, x.max(6) - 6..=x) {
+        if !jumpable(x, y, grid, x.max(JUMP_DISTANCE) - JUMP_DISTANCE..=x) {
             return false;
         }
     }
 
     for (x, y) in jump_test_right {
-        if !jumpable(x, y, grid, x..=(x + 6).min(width - 1)) {
+        if !jumpable(x, y, grid, x..=(x + JUMP_DISTANCE).min(width - 1)) {
             return false;
         }
     }
@@ -165,9 +280,20 @@ fn valid_map(grid: &[Vec<bool>]) -> bool {
 
     // x and y can't be outside of grid, because of the border walls
     while let Some((x, y)) = open.pop() {
-        for neighbor in &[(x - 1, y), (x, y - 1), (x + 1, y), (x, y + 1)] {
-            let (nx, ny) = *neighbor;
-
+        let linked = portals.iter().filter_map(|&(a, b)| {
+            if a == (x, y) {
+                Some(b)
+            } else if b == (x, y) {
+                Some(a)
+            } else {
+                None
+            }
+        });
+        let neighbors: Vec<_> = vec![(x - 1, y), (x, y - 1), (x + 1, y), (x, y + 1)]
+            .into_iter()
+            .chain(linked)
+            .collect();
+        for (nx, ny) in neighbors {
             if !grid[nx][ny] && !closed.contains(&(nx, ny)) {
                 open.push((nx, ny));
                 closed.insert((nx, ny));
@@ -197,10 +323,27 @@ fn jumpable(x: usize, y: usize, grid: &[Vec<bool>], range: RangeInclusive<usize>
         while bottom < grid[0].len() && !grid[tx][bottom] {
             bottom += 1;
         }
-        lowest = lowest.min(bottom - 1 - y);
+
+        let height = bottom - 1 - y;
+        if wall_jumpable(tx, y, height, grid) {
+            return true;
+        }
+        lowest = lowest.min(height);
     }
 
-    lowest <= 8
+    lowest <= JUMP_HEIGHT
+}
+
+/// Whether a player standing `height` cells below row `y` in column `x` can
+/// reach row `y` by jumping up to a wall next to the column and wall-jumping
+/// off it.
+fn wall_jumpable(x: usize, y: usize, height: usize, grid: &[Vec<bool>]) -> bool {
+    let floor = y + height;
+    let lowest_contact = floor.min(y + WALL_JUMP_HEIGHT);
+    let highest_contact = floor.saturating_sub(JUMP_HEIGHT).max(y);
+
+    (highest_contact..=lowest_contact)
+        .any(|wy| (x > 0 && grid[x - 1][wy]) || (x + 1 < grid.len() && grid[x + 1][wy]))
 }
 
 #[derive(Debug)]
@@ -209,6 +352,7 @@ struct Wall {
     y: u8,
     width: u8,
     height: u8,
+    tile: Tile,
 }
 
 impl Wall {
@@ -218,6 +362,7 @@ impl Wall {
             y,
             width,
             height,
+            tile: Tile::Solid,
         }
     }
 
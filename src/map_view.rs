@@ -1,4 +1,6 @@
 use crate::entity::Bounds;
+use crate::portal::Facing;
+use crate::tile::{Tile, DESTRUCTIBLE_HP};
 use crate::MapController;
 use graphics::{Context, Graphics};
 
@@ -23,14 +25,54 @@ impl MapView {
     pub fn draw<G: Graphics>(&self, controller: &MapController, c: &Context, g: &mut G) {
         use graphics::*;
 
-        let color = [1.0; 4];
-
-        let rect = Rectangle::new(color);
         let border = Rectangle::new_border([0.0, 0.0, 0.0, 1.0], 2.0);
 
         controller.map.all_cells().for_each(|cell| {
-            rect.draw(cell.bounds(), &c.draw_state, c.transform, g);
+            let color = match cell.tile {
+                Tile::Destructible { hp } => {
+                    let health = f32::from(hp) / f32::from(DESTRUCTIBLE_HP);
+                    [0.8, 0.65, 0.45, 0.4 + 0.6 * health]
+                }
+                Tile::OneWay => [0.7, 0.7, 0.7, 1.0],
+                Tile::Bounce => [0.3, 0.9, 0.4, 1.0],
+                Tile::Spikes => [0.9, 0.15, 0.15, 1.0],
+                Tile::Ice => [0.7, 0.9, 1.0, 1.0],
+                Tile::Absorb => [0.35, 0.3, 0.45, 1.0],
+                _ => [1.0; 4],
+            };
+
+            if let Tile::Portal { pair, facing } = cell.tile {
+                // colored by pair, with a bright opening on the facing side
+                let mut color = crate::color::hsv_to_rgb(f32::from(pair) * 0.618_034, 0.7, 0.9);
+                let [x, y, w, h] = cell.bounds();
+                let opening = match facing {
+                    Facing::Up => [x, y, w, h / 4.0],
+                    Facing::Down => [x, y + h * 0.75, w, h / 4.0],
+                    Facing::Left => [x, y, w / 4.0, h],
+                    Facing::Right => [x + w * 0.75, y, w / 4.0, h],
+                };
+                Rectangle::new(color).draw(opening, &c.draw_state, c.transform, g);
+                color[3] = 0.6;
+                Rectangle::new(color).draw(cell.bounds(), &c.draw_state, c.transform, g);
+                return;
+            }
+
+            if cell.tile == Tile::OneWay {
+                // only a thin plank on top, it can be jumped through
+                let [x, y, w, h] = cell.bounds();
+                let plank = [x, y, w, h / 4.0];
+                Rectangle::new(color).draw(plank, &c.draw_state, c.transform, g);
+                return;
+            }
+
+            Rectangle::new(color).draw(cell.bounds(), &c.draw_state, c.transform, g);
             border.draw(cell.bounds(), &c.draw_state, c.transform, g);
         });
+
+        let platform = Rectangle::new([0.6, 0.75, 0.9, 1.0]);
+        for bounds in controller.map.platforms.iter().map(Bounds::bounds) {
+            platform.draw(bounds, &c.draw_state, c.transform, g);
+            border.draw(bounds, &c.draw_state, c.transform, g);
+        }
     }
 }
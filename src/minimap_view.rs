@@ -0,0 +1,81 @@
+use crate::camera::Camera;
+use crate::{MapController, PlayerController};
+use graphics::{Context, Graphics};
+
+/// Width of the minimap in overlay units.
+const WIDTH: f64 = 240.0;
+/// Most height the minimap takes, narrowing it for tall maps.
+const MAX_HEIGHT: f64 = 240.0;
+
+/// Overview of the whole map, shown while the map doesn't fit the window.
+#[derive(Default)]
+pub struct MinimapView {}
+
+impl MinimapView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draws into the top right corner of an overlay of the given `size`.
+    pub fn draw<G: Graphics>(
+        &self,
+        map_controller: &MapController,
+        player_controller: &PlayerController,
+        camera: &Camera,
+        size: [f64; 2],
+        c: &Context,
+        g: &mut G,
+    ) {
+        use graphics::*;
+
+        let [world_width, world_height] = map_controller.map.world_size();
+        let [visible_width, visible_height] = camera.visible();
+        if visible_width >= world_width && visible_height >= world_height {
+            return;
+        }
+
+        let scale = (WIDTH / world_width).min(MAX_HEIGHT / world_height);
+        let transform = c
+            .transform
+            .trans(size[0] - world_width * scale - 10.0, 40.0)
+            .scale(scale, scale);
+
+        Rectangle::new([0.0, 0.0, 0.0, 0.5]).draw(
+            [0.0, 0.0, world_width, world_height],
+            &c.draw_state,
+            transform,
+            g,
+        );
+        for cell in map_controller.map.all_cells() {
+            Rectangle::new([1.0, 1.0, 1.0, 0.6]).draw(
+                [cell.x, cell.y, cell.w, cell.h],
+                &c.draw_state,
+                transform,
+                g,
+            );
+        }
+
+        // players as dots that stay visible however small the map is drawn
+        let dot = 4.0 / scale;
+        for player in player_controller.players.values() {
+            if player.state.lives == 0 {
+                continue;
+            }
+            let x = player.state.x + player.state.width / 2.0 - dot / 2.0;
+            let y = player.state.y + player.state.height / 2.0 - dot / 2.0;
+            Rectangle::new(player.state.color).draw([x, y, dot, dot], &c.draw_state, transform, g);
+        }
+
+        Rectangle::new_border([1.0, 1.0, 1.0, 0.9], 1.0 / scale).draw(
+            [
+                camera.x - visible_width / 2.0,
+                camera.y - visible_height / 2.0,
+                visible_width,
+                visible_height,
+            ],
+            &c.draw_state,
+            transform,
+            g,
+        );
+    }
+}
@@ -1,8 +1,13 @@
+use crate::flag::Flag;
 use crate::network::messages::*;
+use crate::pickup::Pickup;
 use crate::player::Player;
 use crate::shot::Shot;
+use crate::FlagController;
+use crate::GameController;
 use crate::LocalInputController;
 use crate::MapController;
+use crate::PickupController;
 use crate::PlayerController;
 use crate::ShotController;
 use anyhow::anyhow;
@@ -30,6 +35,8 @@ impl ClientController {
         host: SocketAddr,
         local: SocketAddr,
         name: Option<&str>,
+        color: Option<[f32; 4]>,
+        team: Option<u8>,
     ) -> Result<Self, ErrorKind> {
         let mut socket = Socket::bind_with_config(
             local,
@@ -66,7 +73,7 @@ impl ClientController {
 
         let mut tx = socket.get_packet_sender();
         if let Some(name) = name {
-            Self::set_name(&host, name.to_string(), &mut tx);
+            Self::set_name(&host, name.to_string(), color, team, &mut tx);
         }
 
         thread::spawn(move || socket.start_polling());
@@ -78,12 +85,16 @@ impl ClientController {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn event<E: GenericEvent>(
         &mut self,
         e: &E,
+        game_controller: &mut GameController,
         player_controller: &mut PlayerController,
         map_controller: &mut MapController,
         shot_controller: &mut ShotController,
+        pickup_controller: &mut PickupController,
+        flag_controller: &mut FlagController,
         local_input_controller: &mut Option<LocalInputController>,
     ) -> Result<(), anyhow::Error> {
         if e.update_args().is_some() {
@@ -98,9 +109,12 @@ impl ClientController {
             unprocessed_inputs.drain(..).try_for_each(|packet| {
                 Self::process(
                     packet,
+                    game_controller,
                     player_controller,
                     local_input_controller,
                     shot_controller,
+                    pickup_controller,
+                    flag_controller,
                     map_controller,
                 )
             })?;
@@ -123,22 +137,52 @@ impl ClientController {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process(
         packet: ClientBound,
+        game_controller: &mut GameController,
         player_controller: &mut PlayerController,
         local_input_controller: &Option<LocalInputController>,
         shot_controller: &mut ShotController,
+        pickup_controller: &mut PickupController,
+        flag_controller: &mut FlagController,
         map_controller: &mut MapController,
     ) -> Result<(), anyhow::Error> {
         match packet.message {
             ClientBoundMessage::SetNameResponse { accepted } => {
                 if !accepted {
-                    return Err(anyhow!("name already taken"));
+                    return Err(anyhow!("name already taken or game is full"));
                 }
             }
             ClientBoundMessage::SetMap(map) => {
                 map_controller.map = map;
             }
+            ClientBoundMessage::SetRules(rules) => {
+                game_controller.rules = rules;
+            }
+            ClientBoundMessage::GameUpdate(state) => {
+                game_controller.state = state;
+            }
+            ClientBoundMessage::PlatformUpdate(platform_state) => {
+                let platform = map_controller
+                    .map
+                    .platforms
+                    .iter_mut()
+                    .find(|platform| platform.state.id == platform_state.id);
+                if let Some(platform) = platform {
+                    platform.state = platform_state;
+                }
+            }
+            ClientBoundMessage::SetCell { x, y, tile } => {
+                let cell = map_controller
+                    .map
+                    .cells
+                    .get_mut(x as usize)
+                    .and_then(|column| column.get_mut(y as usize));
+                if let Some(cell) = cell {
+                    *cell = tile;
+                }
+            }
             ClientBoundMessage::PlayerUpdate(state, inputs) => {
                 if let Some(player) = player_controller.players.get_mut(&state.name) {
                     println!("overriding player");
@@ -161,8 +205,13 @@ impl ClientController {
                     }
                 } else {
                     println!("creating new player: {:?}", state.name);
-                    let mut player =
-                        Player::new(state.name.clone(), 50.0, 50.0, [0.0, 0.0, 0.0, 1.0]);
+                    let mut player = Player::new(
+                        state.name.clone(),
+                        state.x,
+                        state.y,
+                        state.color,
+                        state.lives,
+                    );
                     player.state = state;
                     player.inputs = inputs;
                     player_controller
@@ -170,6 +219,24 @@ impl ClientController {
                         .insert(player.state.name.clone(), player);
                 }
             }
+            ClientBoundMessage::AmmoUpdate(ammo) => {
+                let player = local_input_controller
+                    .as_ref()
+                    .and_then(|l| player_controller.players.get_mut(&l.local_player));
+                if let Some(player) = player {
+                    player.ammo = ammo;
+                }
+            }
+            ClientBoundMessage::PickupUpdate(pickup_state) => {
+                pickup_controller
+                    .pickups
+                    .insert(pickup_state.id, Pickup::from_state(pickup_state));
+            }
+            ClientBoundMessage::FlagUpdate(flag_state) => {
+                flag_controller
+                    .flags
+                    .insert(flag_state.team, Flag::from_state(flag_state));
+            }
             ClientBoundMessage::ShotUpdate(shot_state) => {
                 shot_controller
                     .shots
@@ -189,8 +256,14 @@ impl ClientController {
         Ok(())
     }
 
-    fn set_name(host: &SocketAddr, name: String, tx: &mut Sender<Packet>) {
-        let msg = ServerBoundMessage::SetName(name);
+    fn set_name(
+        host: &SocketAddr,
+        name: String,
+        color: Option<[f32; 4]>,
+        team: Option<u8>,
+        tx: &mut Sender<Packet>,
+    ) {
+        let msg = ServerBoundMessage::SetName { name, color, team };
         let packet = Packet::reliable_unordered(*host, bincode::serialize(&msg).unwrap());
         tx.send(packet).unwrap();
     }
@@ -1,6 +1,10 @@
+use crate::color;
 use crate::network::messages::*;
 use crate::player::Player;
-use crate::{Map, MapController, PlayerController, ShotController};
+use crate::{
+    FlagController, GameController, MapController, PickupController, PlayerController,
+    ShotController,
+};
 use crossbeam_channel::Sender;
 use laminar::{ErrorKind, Packet, Socket, SocketEvent};
 use piston::input::GenericEvent;
@@ -77,15 +81,27 @@ impl HostController {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn event<E: GenericEvent>(
         &mut self,
         e: &E,
+        game_controller: &mut GameController,
         player_controller: &mut PlayerController,
         shot_controller: &mut ShotController,
+        pickup_controller: &mut PickupController,
+        flag_controller: &mut FlagController,
         map_controller: &mut MapController,
     ) {
-        if e.update_args().is_some() {
-            self.update_game_state(player_controller, shot_controller, map_controller);
+        if let Some(tick) = e.update_args() {
+            self.update_game_state(
+                tick.dt,
+                game_controller,
+                player_controller,
+                shot_controller,
+                pickup_controller,
+                flag_controller,
+                map_controller,
+            );
 
             let Self {
                 unprocessed_inputs,
@@ -97,6 +113,12 @@ impl HostController {
             let mut unprocessed_inputs = unprocessed_inputs.lock().unwrap();
             let mut players = players.lock().unwrap();
 
+            if game_controller.dirty {
+                game_controller.dirty = false;
+                let msg = ClientBoundMessage::GameUpdate(game_controller.state.clone());
+                Self::broadcast_reliable(tx, &players, &msg);
+            }
+
             for player in player_controller.players.values_mut().filter(|p| p.dirty) {
                 player.dirty = false;
                 let msg =
@@ -107,10 +129,57 @@ impl HostController {
                 }
             }
 
+            for player in player_controller
+                .players
+                .values_mut()
+                .filter(|p| p.ammo_dirty)
+            {
+                player.ammo_dirty = false;
+                let msg = ClientBoundMessage::AmmoUpdate(player.ammo.clone());
+                let owner = players.iter().find(|(_, name)| **name == player.state.name);
+                if let Some((socket, _)) = owner {
+                    Self::send_reliable(tx, socket, &msg);
+                }
+            }
+
             unprocessed_inputs.drain(..).for_each(|packet| {
-                Self::process(packet, &mut players, player_controller, map_controller, tx)
+                Self::process(
+                    packet,
+                    &mut players,
+                    game_controller,
+                    player_controller,
+                    pickup_controller,
+                    flag_controller,
+                    map_controller,
+                    tx,
+                )
             });
 
+            let map = &mut map_controller.map;
+            for (x, y) in map.changed_cells.drain(..) {
+                let tile = map.cells[x as usize][y as usize];
+                let msg = ClientBoundMessage::SetCell { x, y, tile };
+                Self::broadcast_reliable(tx, &players, &msg);
+            }
+
+            for platform in map.platforms.iter_mut().filter(|p| p.dirty) {
+                platform.dirty = false;
+                let msg = ClientBoundMessage::PlatformUpdate(platform.state.clone());
+                Self::broadcast_reliable(tx, &players, &msg);
+            }
+
+            for pickup in pickup_controller.pickups.values_mut().filter(|p| p.dirty) {
+                pickup.dirty = false;
+                let msg = ClientBoundMessage::PickupUpdate(pickup.state.clone());
+                Self::broadcast_reliable(tx, &players, &msg);
+            }
+
+            for flag in flag_controller.flags.values_mut().filter(|f| f.dirty) {
+                flag.dirty = false;
+                let msg = ClientBoundMessage::FlagUpdate(flag.state.clone());
+                Self::broadcast_reliable(tx, &players, &msg);
+            }
+
             for shot in shot_controller.shots.values_mut().filter(|shot| shot.dirty) {
                 shot.dirty = false;
                 let msg = ClientBoundMessage::ShotUpdate(shot.state.clone());
@@ -122,29 +191,38 @@ impl HostController {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn update_game_state(
         &self,
+        dt: f64,
+        game_controller: &mut GameController,
         player_controller: &mut PlayerController,
         shot_controller: &mut ShotController,
+        pickup_controller: &mut PickupController,
+        flag_controller: &mut FlagController,
         map_controller: &mut MapController,
     ) {
-        let players_alive = player_controller
-            .players
-            .values()
-            .filter(|player| player.state.lives > 0)
-            .count();
-        let player_count = player_controller.players.len();
-
-        if player_count > 1 && players_alive <= 1 {
-            map_controller.map = Map::new();
+        game_controller.respawn(player_controller, &map_controller.map, dt);
+        if game_controller.update(player_controller, dt) {
+            map_controller.reset();
+            game_controller.start_round(&map_controller.map);
             let Self { players, tx, .. } = self;
             let map = ClientBoundMessage::SetMap(map_controller.map.clone());
             Self::broadcast_reliable(tx, &players.lock().unwrap(), &map);
 
+            let lives = game_controller.rules.lives;
             player_controller.players.values_mut().for_each(|player| {
-                player.state.lives = 20;
+                player.state.lives = lives;
+                player.state.effects = Default::default();
+                player.state.carrying_flag = false;
+                player.respawn_in = None;
+                player.last_attacker = None;
                 player.dirty = true;
             });
+            pickup_controller.clear();
+            if !flag_controller.flags.is_empty() {
+                flag_controller.reset(&map_controller.map);
+            }
             for shot in shot_controller.shots.values_mut() {
                 shot.state.lives = 0;
                 shot.dirty = true;
@@ -169,30 +247,41 @@ impl HostController {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process(
         packet: ServerBound,
         players: &mut HashMap<SocketAddr, String>,
+        game_controller: &GameController,
         player_controller: &mut PlayerController,
+        pickup_controller: &PickupController,
+        flag_controller: &FlagController,
         map_controller: &MapController,
         tx: &mut Sender<Packet>,
     ) {
         let player = Self::get_player(packet.player_name, player_controller);
         match packet.message {
-            ServerBoundMessage::SetName(name) => {
-                let accepted = Self::set_name(&name, packet.source, player_controller, tx);
-                if accepted {
-                    if let Some(color) = player_controller.get_free_color() {
-                        players.insert(packet.source, name.clone());
-                        let player = Player::new(name.clone(), 100.0, 100.0, color);
-                        player_controller.players.insert(name.clone(), player);
-                        let player = player_controller.players.get(&name).unwrap();
-
-                        let new_player = ClientBoundMessage::PlayerUpdate(
-                            player.state.clone(),
-                            player.inputs.clone(),
-                        );
-                        Self::broadcast_reliable(tx, players, &new_player);
-                    }
+            ServerBoundMessage::SetName { name, color, team } => {
+                let color = color.and_then(color::sanitize);
+                let team = game_controller.assign_team(team, player_controller);
+                let color = match team {
+                    Some(team) => game_controller.member_color(team, player_controller),
+                    None => player_controller.get_free_color(color),
+                };
+                if let Some(color) =
+                    Self::set_name(&name, color, packet.source, player_controller, tx)
+                {
+                    players.insert(packet.source, name.clone());
+                    let lives = game_controller.rules.lives;
+                    let mut player = Player::new(name.clone(), 100.0, 100.0, color, lives);
+                    player.state.team = team;
+                    player_controller.players.insert(name.clone(), player);
+                    let player = player_controller.players.get(&name).unwrap();
+
+                    let new_player = ClientBoundMessage::PlayerUpdate(
+                        player.state.clone(),
+                        player.inputs.clone(),
+                    );
+                    Self::broadcast_reliable(tx, players, &new_player);
                 }
             }
             ServerBoundMessage::UpdateInputs(inputs) => {
@@ -204,6 +293,10 @@ impl HostController {
             ServerBoundMessage::Connect => {
                 let map = ClientBoundMessage::SetMap(map_controller.map.clone());
                 Self::send_reliable(tx, &packet.source, &map);
+                let rules = ClientBoundMessage::SetRules(game_controller.rules.clone());
+                Self::send_reliable(tx, &packet.source, &rules);
+                let game = ClientBoundMessage::GameUpdate(game_controller.state.clone());
+                Self::send_reliable(tx, &packet.source, &game);
 
                 for player in player_controller.players.values() {
                     let msg = ClientBoundMessage::PlayerUpdate(
@@ -212,26 +305,43 @@ impl HostController {
                     );
                     Self::send_reliable(tx, &packet.source, &msg);
                 }
+
+                for pickup in pickup_controller.pickups.values() {
+                    let msg = ClientBoundMessage::PickupUpdate(pickup.state.clone());
+                    Self::send_reliable(tx, &packet.source, &msg);
+                }
+
+                for flag in flag_controller.flags.values() {
+                    let msg = ClientBoundMessage::FlagUpdate(flag.state.clone());
+                    Self::send_reliable(tx, &packet.source, &msg);
+                }
             }
             ServerBoundMessage::Disconnect => {}
         }
     }
 
+    /// Answers a join request, returning the color resolved for the player if
+    /// the join was accepted. Clients learn the color from the PlayerUpdate
+    /// sent next.
     fn set_name(
         name: &str,
+        color: Option<[f32; 4]>,
         source: SocketAddr,
         player_controller: &mut PlayerController,
         tx: &mut Sender<Packet>,
-    ) -> bool {
-        let accepted = !player_controller
+    ) -> Option<[f32; 4]> {
+        let name_free = !player_controller
             .players
             .keys()
             .any(|exisiting_name| name == exisiting_name);
+        let color = color.filter(|_| name_free);
 
-        let response = ClientBoundMessage::SetNameResponse { accepted };
+        let response = ClientBoundMessage::SetNameResponse {
+            accepted: color.is_some(),
+        };
         Self::send_reliable(tx, &source, &response);
 
-        accepted
+        color
     }
 
     fn get_player(
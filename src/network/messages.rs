@@ -1,9 +1,15 @@
-use crate::{map, player, shot};
+use crate::rules::Rules;
+use crate::tile::Tile;
+use crate::{flag, game, map, pickup, platform, player, shot};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum ServerBoundMessage {
-    SetName(String),
+    SetName {
+        name: String,
+        color: Option<[f32; 4]>,
+        team: Option<u8>,
+    },
     UpdateInputs(player::Inputs),
     Connect,
     Disconnect,
@@ -13,6 +19,13 @@ pub enum ServerBoundMessage {
 pub enum ClientBoundMessage {
     SetNameResponse { accepted: bool },
     SetMap(map::Map),
+    SetRules(Rules),
+    GameUpdate(game::State),
+    SetCell { x: u8, y: u8, tile: Tile },
+    PlatformUpdate(platform::State),
     PlayerUpdate(player::State, player::Inputs),
     ShotUpdate(shot::State),
+    AmmoUpdate(player::Ammo),
+    PickupUpdate(pickup::State),
+    FlagUpdate(flag::State),
 }
@@ -0,0 +1,20 @@
+/// A short-lived speck drawn for effects, it never affects the game.
+#[derive(Clone, Debug)]
+pub struct Particle {
+    pub x: f64,
+    pub y: f64,
+    pub dx: f64,
+    pub dy: f64,
+    pub size: f64,
+    pub color: [f32; 4],
+    /// Remaining time until the particle disappears.
+    pub life: f64,
+    pub max_life: f64,
+}
+
+impl Particle {
+    /// How much of its life the particle has left, from 1 down to 0.
+    pub fn fade(&self) -> f32 {
+        (self.life / self.max_life).clamp(0.0, 1.0) as f32
+    }
+}
@@ -0,0 +1,167 @@
+use crate::map_generator::{JUMP_DISTANCE, JUMP_HEIGHT};
+use crate::tile::Tile;
+use crate::Map;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Grid position of a cell a player can stand in.
+pub type Node = (usize, usize);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Move {
+    Walk,
+    /// Walking off a ledge and dropping down.
+    Fall,
+    Jump,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Step {
+    pub node: Node,
+    pub kind: Move,
+}
+
+/// Routes through the map for players, who can only walk on floors, drop
+/// off ledges and jump as far as `map_generator` assumes.
+pub struct Pathfinder {
+    cells: Vec<Vec<Tile>>,
+    edges: HashMap<Node, Vec<(Node, Move, u32)>>,
+}
+
+impl Pathfinder {
+    pub fn new(map: &Map) -> Self {
+        let mut pathfinder = Self {
+            cells: map.cells.clone(),
+            edges: HashMap::new(),
+        };
+
+        let width = pathfinder.cells.len();
+        let height = pathfinder.cells[0].len();
+        for x in 0..width {
+            for y in 0..height {
+                if pathfinder.is_node((x, y)) {
+                    let edges = pathfinder.edges_from((x, y));
+                    pathfinder.edges.insert((x, y), edges);
+                }
+            }
+        }
+
+        pathfinder
+    }
+
+    /// Whether the map changed since the graph was built, e.g. because walls
+    /// were destroyed.
+    pub fn is_outdated(&self, map: &Map) -> bool {
+        self.cells != map.cells
+    }
+
+    /// The node below the given cell a falling player lands on.
+    pub fn landing(&self, (x, mut y): (usize, usize)) -> Option<Node> {
+        while self.is_free(x, y) {
+            if self.is_node((x, y)) {
+                return Some((x, y));
+            }
+            y += 1;
+        }
+        None
+    }
+
+    /// Cheapest route between two nodes, without the start.
+    pub fn find(&self, from: Node, to: Node) -> Option<Vec<Step>> {
+        let mut costs = HashMap::new();
+        let mut previous: HashMap<Node, Step> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        costs.insert(from, 0);
+        open.push(Reverse((0, from)));
+
+        while let Some(Reverse((cost, node))) = open.pop() {
+            if node == to {
+                let mut path = vec![];
+                let mut current = to;
+                while current != from {
+                    let step = previous[&current];
+                    path.push(Step {
+                        node: current,
+                        kind: step.kind,
+                    });
+                    current = step.node;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if cost > costs[&node] {
+                continue;
+            }
+
+            for &(next, kind, edge_cost) in self.edges.get(&node).into_iter().flatten() {
+                let next_cost = cost + edge_cost;
+                if costs.get(&next).is_none_or(|&known| next_cost < known) {
+                    costs.insert(next, next_cost);
+                    previous.insert(next, Step { node, kind });
+                    open.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn is_free(&self, x: usize, y: usize) -> bool {
+        match self.cells.get(x).and_then(|column| column.get(y)) {
+            // portals would send the player elsewhere
+            Some(tile) => !tile.blocks_players() && !matches!(tile, Tile::Portal { .. }),
+            None => false,
+        }
+    }
+
+    fn is_node(&self, (x, y): Node) -> bool {
+        self.is_free(x, y)
+            && self
+                .cells
+                .get(x)
+                .and_then(|column| column.get(y + 1))
+                .is_some_and(|below| below.is_floor())
+    }
+
+    fn edges_from(&self, (x, y): Node) -> Vec<(Node, Move, u32)> {
+        let width = self.cells.len();
+        let mut edges = vec![];
+
+        for nx in [x.wrapping_sub(1), x + 1].iter().copied() {
+            if nx >= width || !self.is_free(nx, y) {
+                continue;
+            }
+            if self.is_node((nx, y)) {
+                edges.push(((nx, y), Move::Walk, 10));
+            } else if let Some(landing) = self.landing((nx, y)) {
+                let drop = (landing.1 - y) as u32;
+                edges.push((landing, Move::Fall, 10 + 5 * drop));
+            }
+        }
+
+        let columns = x.saturating_sub(JUMP_DISTANCE)..=(x + JUMP_DISTANCE).min(width - 1);
+        let rows = y.saturating_sub(JUMP_HEIGHT)..=y + JUMP_HEIGHT;
+        for tx in columns {
+            for ty in rows.clone() {
+                if (tx, ty) == (x, y) || !self.is_node((tx, ty)) || !self.can_jump((x, y), (tx, ty))
+                {
+                    continue;
+                }
+                let distance = (tx as i32 - x as i32).abs() + (ty as i32 - y as i32).abs();
+                edges.push(((tx, ty), Move::Jump, 20 + 10 * distance as u32));
+            }
+        }
+
+        edges
+    }
+
+    /// Checks the space for rising in the start column, crossing over at the
+    /// height of the higher node and dropping into the target column.
+    fn can_jump(&self, (x, y): Node, (tx, ty): Node) -> bool {
+        let apex = y.min(ty);
+        let rise = (apex..=y).all(|row| self.is_free(x, row));
+        let cross = (x.min(tx)..=x.max(tx)).all(|column| self.is_free(column, apex));
+        let drop = (apex..=ty).all(|row| self.is_free(tx, row));
+        rise && cross && drop
+    }
+}
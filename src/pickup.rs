@@ -0,0 +1,92 @@
+use crate::entity::Bounds;
+use crate::Player;
+use serde::{Deserialize, Serialize};
+
+pub const SIZE: f64 = 20.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Kind {
+    ExtraLife,
+    Shield,
+    SpeedBoost,
+    TripleShot,
+    ExtraBounces,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 5] = [
+        Kind::ExtraLife,
+        Kind::Shield,
+        Kind::SpeedBoost,
+        Kind::TripleShot,
+        Kind::ExtraBounces,
+    ];
+
+    pub fn color(self) -> [f32; 4] {
+        match self {
+            Kind::ExtraLife => [1.0, 0.25, 0.3, 1.0],
+            Kind::Shield => [0.4, 0.8, 1.0, 1.0],
+            Kind::SpeedBoost => [1.0, 0.9, 0.2, 1.0],
+            Kind::TripleShot => [1.0, 0.55, 0.1, 1.0],
+            Kind::ExtraBounces => [0.7, 0.35, 1.0, 1.0],
+        }
+    }
+
+    pub fn apply(self, player: &mut Player) {
+        let effects = &mut player.state.effects;
+        match self {
+            Kind::ExtraLife => player.state.lives = player.state.lives.saturating_add(1),
+            Kind::Shield => effects.shield = 8.0,
+            Kind::SpeedBoost => effects.speed = 8.0,
+            Kind::TripleShot => effects.triple_shot = 10.0,
+            Kind::ExtraBounces => effects.extra_bounces = 10.0,
+        }
+        player.dirty = true;
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct State {
+    pub id: u32,
+    pub kind: Kind,
+    pub x: f64,
+    pub y: f64,
+    pub collected: bool,
+}
+
+#[derive(Debug)]
+pub struct Pickup {
+    pub state: State,
+    pub dirty: bool,
+}
+
+impl Pickup {
+    pub fn new(id: u32, kind: Kind, x: f64, y: f64) -> Self {
+        Self::from_state(State {
+            id,
+            kind,
+            x,
+            y,
+            collected: false,
+        })
+    }
+
+    pub fn from_state(state: State) -> Self {
+        Self { state, dirty: true }
+    }
+}
+
+impl Bounds for Pickup {
+    fn x(&self) -> f64 {
+        self.state.x
+    }
+    fn y(&self) -> f64 {
+        self.state.y
+    }
+    fn w(&self) -> f64 {
+        SIZE
+    }
+    fn h(&self) -> f64 {
+        SIZE
+    }
+}
@@ -0,0 +1,96 @@
+use crate::collision;
+use crate::pickup;
+use crate::pickup::{Kind, Pickup};
+use crate::{Map, PlayerController};
+use piston::input::GenericEvent;
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+
+const MAX_PICKUPS: usize = 4;
+const SPAWN_INTERVAL: f64 = 8.0;
+
+#[derive(Default)]
+pub struct PickupController {
+    pub pickups: HashMap<u32, Pickup>,
+    next_id: u32,
+    spawn_timer: f64,
+    /// Only the authoritative side spawns and collects pickups.
+    authoritative: bool,
+}
+
+impl PickupController {
+    pub fn new(authoritative: bool) -> Self {
+        Self {
+            authoritative,
+            ..Self::default()
+        }
+    }
+
+    pub fn event<E: GenericEvent>(
+        &mut self,
+        map: &Map,
+        player_controller: &mut PlayerController,
+        e: &E,
+    ) {
+        if let Some(tick) = e.update_args() {
+            self.pickups.retain(|_, pickup| !pickup.state.collected);
+
+            if !self.authoritative {
+                return;
+            }
+
+            self.spawn_timer += tick.dt;
+            if self.spawn_timer >= SPAWN_INTERVAL {
+                self.spawn_timer = 0.0;
+                self.spawn(map);
+            }
+
+            for pickup in self.pickups.values_mut() {
+                let player = player_controller
+                    .players
+                    .values_mut()
+                    .filter(|player| player.state.lives > 0)
+                    .find(|player| collision::collides(*player, pickup));
+                if let Some(player) = player {
+                    pickup.state.kind.apply(player);
+                    pickup.state.collected = true;
+                    pickup.dirty = true;
+                }
+            }
+        }
+    }
+
+    fn spawn(&mut self, map: &Map) {
+        if self.pickups.len() >= MAX_PICKUPS {
+            return;
+        }
+
+        let mut rng = rand::thread_rng();
+        let free: Vec<_> = map
+            .floor_cells()
+            .filter(|cell| {
+                !self
+                    .pickups
+                    .values()
+                    .any(|pickup| collision::collides(cell, pickup))
+            })
+            .collect();
+
+        if let (Some(cell), Some(&kind)) = (free.choose(&mut rng), Kind::ALL.choose(&mut rng)) {
+            // stand on the floor in the middle of the cell
+            let x = cell.x + (cell.w - pickup::SIZE) / 2.0;
+            let y = cell.y + cell.h - pickup::SIZE;
+            let pickup = Pickup::new(self.next_id, kind, x, y);
+            self.pickups.insert(self.next_id, pickup);
+            self.next_id += 1;
+        }
+    }
+
+    /// Removes all pickups, e.g. when a new round starts.
+    pub fn clear(&mut self) {
+        for pickup in self.pickups.values_mut() {
+            pickup.state.collected = true;
+            pickup.dirty = true;
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::entity::Bounds;
+use crate::PickupController;
+use graphics::{Context, Graphics};
+
+#[derive(Default)]
+pub struct PickupView {}
+
+impl PickupView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn draw<G: Graphics>(&self, controller: &PickupController, c: &Context, g: &mut G) {
+        use graphics::*;
+
+        for pickup in controller.pickups.values() {
+            let color = pickup.state.kind.color();
+            let [x, y, w, h] = pickup.bounds();
+
+            Ellipse::new_border(color, 2.0).draw([x, y, w, h], &c.draw_state, c.transform, g);
+            Ellipse::new(color).draw(
+                [x + w / 4.0, y + h / 4.0, w / 2.0, h / 2.0],
+                &c.draw_state,
+                c.transform,
+                g,
+            );
+        }
+    }
+}
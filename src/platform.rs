@@ -0,0 +1,107 @@
+use crate::entity::{Bounds, Speed};
+use serde::{Deserialize, Serialize};
+
+const SPEED: f64 = 120.0;
+
+/// Platform as defined in a map, in grid coordinates.
+#[derive(Clone, Debug)]
+pub struct Path {
+    /// Width in cells.
+    pub width: u8,
+    /// Top-left cells the platform moves between, returning to the first one.
+    pub points: Vec<(u8, u8)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct State {
+    pub id: u32,
+    pub x: f64,
+    pub y: f64,
+    /// Index of the path point the platform is heading to.
+    pub target: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Platform {
+    pub state: State,
+    pub w: f64,
+    pub h: f64,
+    pub path: Vec<[f64; 2]>,
+    #[serde(skip)]
+    pub dx: f64,
+    #[serde(skip)]
+    pub dy: f64,
+    #[serde(skip)]
+    pub dirty: bool,
+}
+
+impl Platform {
+    pub fn new(id: u32, w: f64, h: f64, path: Vec<[f64; 2]>) -> Self {
+        let [x, y] = path[0];
+        Self {
+            state: State {
+                id,
+                x,
+                y,
+                target: 1 % path.len(),
+            },
+            w,
+            h,
+            path,
+            dx: 0.0,
+            dy: 0.0,
+            dirty: true,
+        }
+    }
+
+    /// Moves towards the next path point, turning to the following one when
+    /// it is reached.
+    pub fn advance(&mut self, dt: f64) {
+        let [target_x, target_y] = self.path[self.state.target];
+        let distance_x = target_x - self.state.x;
+        let distance_y = target_y - self.state.y;
+        let distance = distance_x.hypot(distance_y);
+
+        if distance <= SPEED * dt {
+            self.state.x = target_x;
+            self.state.y = target_y;
+            let next = (self.state.target + 1) % self.path.len();
+            // clients only need to hear about actual turns
+            if self.path[next] != self.path[self.state.target] {
+                self.dirty = true;
+            }
+            self.state.target = next;
+            self.dx = distance_x / dt;
+            self.dy = distance_y / dt;
+        } else {
+            self.dx = SPEED * distance_x / distance;
+            self.dy = SPEED * distance_y / distance;
+            self.state.x += self.dx * dt;
+            self.state.y += self.dy * dt;
+        }
+    }
+}
+
+impl Bounds for Platform {
+    fn x(&self) -> f64 {
+        self.state.x
+    }
+    fn y(&self) -> f64 {
+        self.state.y
+    }
+    fn w(&self) -> f64 {
+        self.w
+    }
+    fn h(&self) -> f64 {
+        self.h
+    }
+}
+
+impl Speed for Platform {
+    fn dx(&self) -> f64 {
+        self.dx
+    }
+    fn dy(&self) -> f64 {
+        self.dy
+    }
+}
@@ -0,0 +1,48 @@
+use crate::collision;
+use crate::{Map, PlayerController};
+use piston::input::GenericEvent;
+
+#[derive(Default)]
+pub struct PlatformController {}
+
+impl PlatformController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn event<E: GenericEvent>(
+        &mut self,
+        map: &mut Map,
+        player_controller: &mut PlayerController,
+        e: &E,
+    ) {
+        if let Some(tick) = e.update_args() {
+            for platform in &mut map.platforms {
+                platform.advance(tick.dt);
+            }
+
+            for player in player_controller.players.values_mut() {
+                if player.state.lives == 0 {
+                    continue;
+                }
+
+                let riding = player
+                    .on_platform
+                    .and_then(|id| map.platforms.iter().find(|p| p.state.id == id));
+                if let Some(platform) = riding {
+                    player.state.x += platform.dx * tick.dt;
+                    player.state.y += platform.dy * tick.dt;
+                    continue;
+                }
+
+                // push players out of the way instead of moving into them
+                for platform in &map.platforms {
+                    if collision::collides(player, platform) {
+                        player.state.x += platform.dx * tick.dt;
+                        player.state.y += platform.dy * tick.dt;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::entity::{Bounds, Speed};
+use crate::weapon::Weapon;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -9,32 +10,144 @@ pub struct State {
     pub width: f64,
     pub height: f64,
     pub color: [f32; 4],
+    pub team: Option<u8>,
     pub dx: f64,
     pub dy: f64,
     pub lives: u8,
+    pub effects: Effects,
+    /// Remaining time of an active dash.
+    pub dash: f64,
+    pub dash_cooldown: f64,
+    /// Remaining time the player can't be hit.
+    pub invulnerable: f64,
+    /// Flag carriers are slower and can't shoot.
+    pub carrying_flag: bool,
 }
 
+/// Remaining durations of power-ups in seconds.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Effects {
+    pub shield: f64,
+    pub speed: f64,
+    pub triple_shot: f64,
+    pub extra_bounces: f64,
+}
+
+impl Effects {
+    pub fn tick(&mut self, dt: f64) {
+        for timer in &mut [
+            &mut self.shield,
+            &mut self.speed,
+            &mut self.triple_shot,
+            &mut self.extra_bounces,
+        ] {
+            **timer = (**timer - dt).max(0.0);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Inputs {
     pub left: bool,
     pub right: bool,
     pub jump: bool,
+    pub dash: bool,
     pub shoot: bool,
+    pub weapon: Weapon,
     pub mouse_x: f64,
     pub mouse_y: f64,
 }
 
+/// Magazine of the currently equipped weapon, only known to the host and the
+/// owning client.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ammo {
+    pub weapon: Weapon,
+    pub loaded: u32,
+    pub cooldown: f64,
+    pub reload: f64,
+}
+
+impl Ammo {
+    pub fn new(weapon: Weapon) -> Self {
+        Self {
+            weapon,
+            loaded: weapon.magazine(),
+            cooldown: 0.0,
+            reload: 0.0,
+        }
+    }
+
+    /// Advances cooldown and reload timers, returns whether the magazine
+    /// changed.
+    pub fn tick(&mut self, weapon: Weapon, dt: f64) -> bool {
+        let mut changed = false;
+
+        if weapon != self.weapon {
+            self.weapon = weapon;
+            self.loaded = 0;
+            self.reload = weapon.reload_time();
+            changed = true;
+        }
+
+        self.cooldown = (self.cooldown - dt).max(0.0);
+
+        if self.reload > 0.0 {
+            self.reload -= dt;
+            if self.reload <= 0.0 {
+                self.reload = 0.0;
+                self.loaded = weapon.magazine();
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    pub fn can_fire(&self) -> bool {
+        self.loaded > 0 && self.cooldown <= 0.0 && self.reload <= 0.0
+    }
+
+    /// Consumes one shot, starting a reload when the magazine is empty.
+    pub fn fire(&mut self) {
+        self.loaded -= 1;
+        self.cooldown = self.weapon.cooldown();
+        if self.loaded == 0 {
+            self.reload = self.weapon.reload_time();
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WallSide {
+    Left,
+    Right,
+}
+
 #[derive(Debug)]
 pub struct Player {
     pub state: State,
     pub inputs: Inputs,
+    pub ammo: Ammo,
     pub on_ground: bool,
+    pub on_ice: bool,
+    /// Id of the moving platform the player stands on.
+    pub on_platform: Option<u32>,
+    pub on_wall: Option<WallSide>,
+    /// Remaining time horizontal inputs are ignored after a wall-jump.
+    pub wall_jump: f64,
     pub has_double_jump: bool,
+    /// Player who hit this one last, only tracked on the host.
+    pub last_attacker: Option<String>,
+    /// Remaining time until an eliminated player respawns, only tracked on
+    /// the host.
+    pub respawn_in: Option<f64>,
     pub dirty: bool,
+    pub ammo_dirty: bool,
 }
 
 impl Player {
-    pub fn new(name: String, x: f64, y: f64, color: [f32; 4]) -> Self {
+    pub fn new(name: String, x: f64, y: f64, color: [f32; 4], lives: u8) -> Self {
         Self {
             state: State {
                 name,
@@ -43,17 +156,54 @@ impl Player {
                 width: 20.0,
                 height: 20.0,
                 color,
+                team: None,
                 dx: 0.0,
                 dy: 0.0,
-                lives: 20,
+                lives,
+                effects: Effects::default(),
+                dash: 0.0,
+                dash_cooldown: 0.0,
+                invulnerable: 0.0,
+                carrying_flag: false,
             },
             inputs: Inputs::default(),
+            ammo: Ammo::new(Weapon::default()),
             on_ground: false,
+            on_ice: false,
+            on_platform: None,
+            on_wall: None,
+            wall_jump: 0.0,
             has_double_jump: true,
+            last_attacker: None,
+            respawn_in: None,
             dirty: true,
+            ammo_dirty: true,
         }
     }
 
+    /// Direction from the center of the player to the mouse.
+    pub fn aim_angle(&self) -> f64 {
+        let center_x = self.state.x + self.state.width / 2.0;
+        let center_y = self.state.y + self.state.height / 2.0;
+        (self.inputs.mouse_y - center_y).atan2(self.inputs.mouse_x - center_x)
+    }
+
+    pub fn is_dashing(&self) -> bool {
+        self.state.dash > 0.0
+    }
+
+    /// Takes lives from the player unless a shield, dash or invulnerability
+    /// is active. Returns whether the player was hurt.
+    pub fn hit(&mut self, damage: u8) -> bool {
+        if self.state.effects.shield > 0.0 || self.is_dashing() || self.state.invulnerable > 0.0 {
+            return false;
+        }
+
+        self.state.lives = self.state.lives.saturating_sub(damage);
+        self.dirty = true;
+        true
+    }
+
     pub fn bounds(&self) -> [f64; 4] {
         [
             self.state.x,
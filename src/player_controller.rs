@@ -1,21 +1,33 @@
 use crate::cell::Cell;
 use crate::collision;
-use crate::collision::Collision;
+use crate::color;
+use crate::game;
+use crate::game::Hit;
 use crate::player;
+use crate::player::WallSide;
+use crate::rules::Rules;
+use crate::spatial_hash::SpatialHash;
+use crate::tile::Tile;
 use crate::{Map, Player, ShotController};
 use piston::input::{Button, ButtonArgs, ButtonState, GenericEvent, Key};
 use std::collections::HashMap;
 
-const COLORS: [[f32; 4]; 8] = [
-    [0.000, 0.772, 0.244, 1.0],
-    [1.000, 0.204, 0.735, 1.0],
-    [1.000, 0.577, 0.094, 1.0],
-    [0.274, 0.637, 1.000, 1.0],
-    [1.000, 1.000, 0.200, 1.0],
-    [0.038, 0.000, 0.764, 1.0],
-    [0.650, 0.033, 0.000, 1.0],
-    [0.254, 0.988, 1.000, 1.0],
-];
+pub const DASH_COOLDOWN: f64 = 2.0;
+const DASH_TIME: f64 = 0.15;
+const DASH_SPEED: f64 = 1100.0;
+const DASH_KNOCKBACK: f64 = 0.8;
+const WALL_SLIDE_SPEED: f64 = 150.0;
+const WALL_JUMP_TIME: f64 = 0.2;
+const BOUNCE_SPEED: f64 = 1100.0;
+const SPIKES_INVULNERABILITY: f64 = 1.0;
+const FLAG_CARRIER_SPEED: f64 = 0.7;
+/// Side of the squares shots are bucketed in when checking hits.
+const HIT_GRID_SIZE: f64 = 64.0;
+/// Most surfaces a player can hit and slide along in a single tick.
+const COLLISION_STEPS: usize = 4;
+
+/// Maximum number of players, limited by the number of distinguishable colors.
+pub const MAX_PLAYERS: usize = 16;
 
 #[derive(Default)]
 pub struct PlayerController {
@@ -30,20 +42,24 @@ impl PlayerController {
     pub fn event<E: GenericEvent>(
         &mut self,
         map: &Map,
+        game: &game::State,
+        rules: &Rules,
         shot_controller: &mut ShotController,
         e: &E,
     ) {
         if let Some(tick) = e.update_args() {
-            let cells: Vec<_> = map.all_cells().collect();
             for player in self.players.values_mut() {
                 if player.state.lives == 0 {
                     continue;
                 }
 
-                Self::update(player, tick.dt);
-                Self::process_collision(player, &cells, tick.dt, shot_controller);
-                Self::motion(player, tick.dt);
+                Self::update(player, rules, tick.dt);
+                Self::process_collision(player, map, tick.dt);
+                Self::teleport(player, map);
             }
+
+            self.process_hits(game, shot_controller);
+            self.knockback();
         }
 
         if let Some(ButtonArgs {
@@ -54,74 +70,259 @@ impl PlayerController {
         {
             self.players
                 .values_mut()
-                .for_each(|player| player.state.lives = 20)
+                .for_each(|player| player.state.lives = rules.lives)
         }
     }
 
-    fn update(player: &mut Player, dt: f64) {
-        let speed = 300.0;
-        if player.inputs.left && !player.inputs.right {
+    fn update(player: &mut Player, rules: &Rules, dt: f64) {
+        player.state.effects.tick(dt);
+        player.state.invulnerable = (player.state.invulnerable - dt).max(0.0);
+
+        if Self::dash(player, dt) {
+            return;
+        }
+
+        let speed = if player.state.effects.speed > 0.0 {
+            rules.boosted_speed
+        } else {
+            rules.speed
+        };
+        let speed = if player.state.carrying_flag {
+            speed * FLAG_CARRIER_SPEED
+        } else {
+            speed
+        };
+        player.wall_jump = (player.wall_jump - dt).max(0.0);
+        if player.wall_jump > 0.0 {
+            // keep the kick away from the wall
+        } else if player.inputs.left && !player.inputs.right {
             player.state.dx = player.state.dx.min(-speed);
         } else if !player.inputs.left && player.inputs.right {
             player.state.dx = player.state.dx.max(speed);
         } else {
-            let friction = if player.on_ground { 16.0 } else { 4.0 };
+            let friction = match (player.on_ground, player.on_ice) {
+                (true, false) => rules.ground_friction,
+                (true, true) => rules.ice_friction,
+                (false, _) => rules.air_friction,
+            };
             player.state.dx -= player.state.dx * friction * dt;
             if player.state.dx.abs() < 0.000_001 {
                 player.state.dx = 0.0;
             }
         }
 
-        if player.inputs.jump && (player.on_ground || player.has_double_jump) {
+        let can_jump = player.on_ground || player.on_wall.is_some() || player.has_double_jump;
+        if player.inputs.jump && can_jump {
             player.inputs.jump = false;
 
             if player.on_ground {
-                player.state.dy = player.state.dy.min(-805.0);
+                player.state.dy = player.state.dy.min(-rules.jump_speed);
+            } else if let Some(wall) = player.on_wall.take() {
+                let away = match wall {
+                    WallSide::Left => 1.0,
+                    WallSide::Right => -1.0,
+                };
+                player.state.dx = away * 450.0;
+                player.state.dy = player.state.dy.min(-rules.wall_jump_speed);
+                player.wall_jump = WALL_JUMP_TIME;
             } else {
                 player.has_double_jump = false;
-                player.state.dy = player.state.dy.min(-405.0);
+                player.state.dy = player.state.dy.min(-rules.double_jump_speed);
             }
         } else {
-            player.state.dy += 1000.0 * dt;
+            player.state.dy += rules.gravity * dt;
+
+            if player.on_wall.is_some() && !player.on_ground {
+                player.state.dy = player.state.dy.min(WALL_SLIDE_SPEED);
+            }
         }
     }
 
-    fn process_collision(
-        player: &mut Player,
-        cells: &[Cell],
-        dt: f64,
-        shot_controller: &mut ShotController,
-    ) {
-        match collision::check(player, cells, dt) {
-            Some(Collision::Side { x, y }) => {
-                if x.is_some() {
+    /// Starts or continues a dash, returns whether the player is dashing.
+    ///
+    /// Dashing players keep their velocity and ignore gravity and inputs.
+    fn dash(player: &mut Player, dt: f64) -> bool {
+        let state = &mut player.state;
+        state.dash_cooldown = (state.dash_cooldown - dt).max(0.0);
+
+        if player.inputs.dash && state.dash_cooldown <= 0.0 {
+            player.inputs.dash = false;
+
+            let center_x = state.x + state.width / 2.0;
+            let center_y = state.y + state.height / 2.0;
+            let angle = (player.inputs.mouse_y - center_y).atan2(player.inputs.mouse_x - center_x);
+            state.dx = DASH_SPEED * angle.cos();
+            state.dy = DASH_SPEED * angle.sin();
+            state.dash = DASH_TIME;
+            state.dash_cooldown = DASH_COOLDOWN;
+            player.dirty = true;
+        }
+
+        if state.dash <= 0.0 {
+            return false;
+        }
+
+        state.dash -= dt;
+        if state.dash <= 0.0 {
+            // don't keep flying across the map after the dash
+            state.dash = 0.0;
+            state.dx *= 0.3;
+            state.dy *= 0.3;
+        }
+        true
+    }
+
+    /// Pushes players away that are hit by a dashing player.
+    fn knockback(&mut self) {
+        let dashes: Vec<_> = self
+            .players
+            .values()
+            .filter(|player| player.state.lives > 0 && player.is_dashing())
+            .map(|player| {
+                (
+                    player.state.name.clone(),
+                    player.bounds(),
+                    player.state.dx,
+                    player.state.dy,
+                )
+            })
+            .collect();
+
+        for (name, bounds, dx, dy) in dashes {
+            for player in self.players.values_mut() {
+                if player.state.name == name
+                    || player.state.lives == 0
+                    || player.is_dashing()
+                    || !collision::collides(player, &bounds)
+                {
+                    continue;
+                }
+
+                player.state.dx = dx * DASH_KNOCKBACK;
+                player.state.dy = dy * DASH_KNOCKBACK - 200.0;
+                player.dirty = true;
+            }
+        }
+    }
+
+    /// Moves the player for `dt`, stopping at walls and platforms in the way
+    /// and sliding along them for the rest of the tick.
+    fn process_collision(player: &mut Player, map: &Map, dt: f64) {
+        player.on_ground = false;
+        player.on_wall = None;
+        player.on_platform = None;
+        let mut touches_spikes = false;
+        let mut remaining = dt;
+        for _ in 0..COLLISION_STEPS {
+            // one-way platforms only hold players coming from above
+            let bottom = player.state.y + player.state.height;
+            let cells: Vec<Cell> = map
+                .cells_in(&collision::swept_bounds(player, remaining))
+                .filter(|cell| {
+                    cell.tile.blocks_players()
+                        || (cell.tile == Tile::OneWay && player.state.dy >= 0.0 && bottom <= cell.y)
+                })
+                .collect();
+            let impact = match collision::sweep(player, &cells, &map.platforms, remaining) {
+                Some(impact) => impact,
+                None => {
+                    Self::motion(player, remaining);
+                    break;
+                }
+            };
+
+            player.state.x = impact.x;
+            player.state.y = impact.y;
+            let tile = impact.obstacle.cell().map(|cell| cell.tile);
+            touches_spikes |= tile == Some(Tile::Spikes);
+            match impact.normal {
+                _ if impact.is_corner() => {
+                    // hit a corner exactly, keep falling past it
                     player.state.dx = 0.0;
                 }
-                if let Some(cell) = y {
-                    if player.state.dy > 0.0 {
-                        player.state.y = cell.y - player.state.height;
+                [nx, _] if nx != 0.0 => {
+                    player.on_wall = Some(if nx > 0.0 {
+                        WallSide::Left
+                    } else {
+                        WallSide::Right
+                    });
+                    player.state.dx = 0.0;
+                }
+                [_, ny] => {
+                    if ny < 0.0 {
                         player.on_ground = true;
+                        player.on_ice = tile == Some(Tile::Ice);
+                        player.on_platform = impact.obstacle.platform().map(|p| p.state.id);
                         player.has_double_jump = true;
                     }
 
-                    player.state.dy = 0.0;
-                } else {
-                    player.on_ground = false;
+                    // only landing on a bounce tile bounces, hitting one from
+                    // below stops like any ceiling
+                    player.state.dy = if ny < 0.0 && tile == Some(Tile::Bounce) {
+                        -BOUNCE_SPEED
+                    } else {
+                        0.0
+                    };
                 }
             }
-            Some(Collision::Corner { .. }) => {
-                player.state.dx = 0.0;
-            }
-            _ => {
-                player.on_ground = false;
+            remaining *= 1.0 - impact.time;
+        }
+
+        if touches_spikes && player.hit(1) {
+            player.state.invulnerable = SPIKES_INVULNERABILITY;
+            player.state.dy = player.state.dy.min(-600.0);
+        }
+    }
+
+    /// Lets shots hit players, teammates are spared or hurt the shooter
+    /// depending on the friendly fire setting.
+    fn process_hits(&mut self, game: &game::State, shot_controller: &mut ShotController) {
+        let teams: HashMap<_, _> = self
+            .players
+            .values()
+            .map(|player| (player.state.name.clone(), player.state.team))
+            .collect();
+        let mut reflected = vec![];
+
+        let mut grid = SpatialHash::new(HIT_GRID_SIZE);
+        for (id, shot) in &shot_controller.shots {
+            grid.insert(id.clone(), shot);
+        }
+
+        for player in self.players.values_mut() {
+            for id in grid.query(player) {
+                let shot = match shot_controller.shots.get_mut(&id) {
+                    Some(shot) => shot,
+                    None => continue,
+                };
+                if player.state.lives == 0
+                    || player.is_dashing()
+                    || !collision::collides(player, shot)
+                {
+                    continue;
+                }
+
+                let owner = &shot.state.id.owner;
+                let shooter_team = teams.get(owner).copied().flatten();
+                let damage = shot.state.weapon.damage();
+                match game.resolve_hit(owner, shooter_team, &player.state) {
+                    Hit::Target => {
+                        shot.state.lives = 0;
+                        player.last_attacker = Some(owner.clone());
+                        player.hit(damage);
+                    }
+                    Hit::Shooter => {
+                        shot.state.lives = 0;
+                        reflected.push((owner.clone(), damage));
+                    }
+                    Hit::Nobody => {}
+                }
             }
         }
 
-        for shot in &mut shot_controller.shots.values_mut() {
-            if player.state.lives > 0 && collision::collides(player, shot) {
-                shot.state.lives = 0;
-                player.state.lives -= 1;
-                player.dirty = true;
+        for (name, damage) in reflected {
+            if let Some(player) = self.players.get_mut(&name) {
+                player.hit(damage);
             }
         }
     }
@@ -133,16 +334,113 @@ impl PlayerController {
         *y += *dy * dt;
     }
 
-    #[allow(clippy::float_cmp)]
-    pub fn get_free_color(&self) -> Option<[f32; 4]> {
-        COLORS
-            .iter()
-            .find(|&color| {
-                !self
-                    .players
-                    .values()
-                    .any(|player| player.state.color == *color)
+    fn teleport(player: &mut Player, map: &Map) {
+        if let Some(transfer) = map.portal_transfer(player) {
+            player.state.x = transfer.x;
+            player.state.y = transfer.y;
+            player.state.dx = transfer.dx;
+            player.state.dy = transfer.dy;
+            player.on_platform = None;
+            player.on_wall = None;
+            player.dirty = true;
+        }
+    }
+
+    /// Resolves the color for a new player.
+    ///
+    /// The preferred color is used if it is distinguishable from all colors in
+    /// use, otherwise the closest free palette color is picked. Returns `None`
+    /// if the game is full.
+    ///
+    /// Every player claims the palette color nearest to theirs, so there is a
+    /// palette color left for each free seat. Unclaimed palette colors close to
+    /// a custom color are only handed out when nothing else is left.
+    pub fn get_free_color(&self, preferred: Option<[f32; 4]>) -> Option<[f32; 4]> {
+        if self.players.len() >= MAX_PLAYERS {
+            return None;
+        }
+
+        let is_distinct = |color: &[f32; 4]| {
+            self.players
+                .values()
+                .all(|player| color::distance(&player.state.color, color) >= color::MIN_DISTANCE)
+        };
+
+        if let Some(preferred) = preferred.filter(is_distinct) {
+            return Some(preferred);
+        }
+
+        let palette = color::palette(MAX_PLAYERS);
+        let nearest = |color: &[f32; 4]| {
+            (0..palette.len()).min_by(|&a, &b| {
+                color::distance(&palette[a], color).total_cmp(&color::distance(&palette[b], color))
             })
-            .copied()
+        };
+        let claimed: Vec<_> = self
+            .players
+            .values()
+            .filter_map(|player| nearest(&player.state.color))
+            .collect();
+        let unclaimed: Vec<_> = palette
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !claimed.contains(i))
+            .map(|(_, &color)| color)
+            .collect();
+
+        let distinct: Vec<_> = unclaimed.iter().copied().filter(is_distinct).collect();
+        let candidates = if distinct.is_empty() {
+            unclaimed
+        } else {
+            distinct
+        };
+        match preferred {
+            Some(preferred) => candidates.into_iter().min_by(|a, b| {
+                color::distance(a, &preferred).total_cmp(&color::distance(b, &preferred))
+            }),
+            None => candidates.into_iter().next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounce_tiles_only_bounce_from_above() {
+        // jumping into the underside stops the player below the tile
+        let map = Map::parse("...\n.^.\n...").unwrap();
+        let mut player = Player::new("a".to_string(), 50.0, 85.0, [1.0; 4], 3);
+        player.state.dy = -600.0;
+        PlayerController::process_collision(&mut player, &map, 0.1);
+        assert_eq!(player.state.dy, 0.0);
+        assert!(player.state.y >= 80.0);
+
+        // landing on top bounces the player up
+        let map = Map::parse("...\n...\n...\n.^.").unwrap();
+        let mut player = Player::new("a".to_string(), 50.0, 95.0, [1.0; 4], 3);
+        player.state.dy = 600.0;
+        PlayerController::process_collision(&mut player, &map, 0.1);
+        assert_eq!(player.state.dy, -BOUNCE_SPEED);
+        assert!(player.state.y + player.state.height <= 120.0);
+    }
+
+    #[test]
+    fn custom_colors_leave_a_color_for_every_seat() {
+        let mut player_controller = PlayerController::new();
+        let custom = color::parse_hex("F90F3D").unwrap();
+        for i in 0..MAX_PLAYERS {
+            let preferred = if i == 0 { Some(custom) } else { None };
+            let color = player_controller.get_free_color(preferred);
+            assert!(color.is_some(), "no color for player {}", i + 1);
+            let player = Player::new(i.to_string(), 0.0, 0.0, color.unwrap(), 1);
+            player_controller.players.insert(i.to_string(), player);
+        }
+        assert_eq!(
+            player_controller.players[&0.to_string()].state.color,
+            custom
+        );
+        assert_eq!(player_controller.get_free_color(None), None);
     }
 }
@@ -1,7 +1,10 @@
 use crate::player;
+use crate::rules::Rules;
 use crate::PlayerController;
 use graphics::{Context, Graphics};
 
+const AIM_LINE_LENGTH: f64 = 24.0;
+
 #[derive(Default)]
 pub struct PlayerView {}
 
@@ -10,7 +13,13 @@ impl PlayerView {
         Self::default()
     }
 
-    pub fn draw<G: Graphics>(&self, controller: &PlayerController, c: &Context, g: &mut G) {
+    pub fn draw<G: Graphics>(
+        &self,
+        controller: &PlayerController,
+        rules: &Rules,
+        c: &Context,
+        g: &mut G,
+    ) {
         use graphics::*;
 
         for player in controller.players.values() {
@@ -21,6 +30,7 @@ impl PlayerView {
                 width,
                 height,
                 lives,
+                ref effects,
                 ..
             } = player.state;
 
@@ -29,7 +39,10 @@ impl PlayerView {
             }
 
             let mut inner_color = color;
-            inner_color[3] = f32::from(lives) / 20.0;
+            inner_color[3] = (f32::from(lives) / f32::from(rules.lives)).min(1.0);
+            if player.state.invulnerable > 0.0 {
+                inner_color[3] *= 0.5;
+            }
             let coords = [x, y, width, height];
 
             Rectangle::new_round(inner_color, 5.0).draw(coords, &c.draw_state, c.transform, g);
@@ -39,6 +52,34 @@ impl PlayerView {
                 c.transform,
                 g,
             );
+
+            // short aim line from the edge of the player towards the mouse
+            let angle = player.aim_angle();
+            let (center_x, center_y) = (x + width / 2.0, y + height / 2.0);
+            let start = width / 2.0 + 4.0;
+            let end = start + AIM_LINE_LENGTH;
+            let mut aim_color = color;
+            aim_color[3] = 0.5;
+            Line::new(aim_color, 1.0).draw(
+                [
+                    center_x + start * angle.cos(),
+                    center_y + start * angle.sin(),
+                    center_x + end * angle.cos(),
+                    center_y + end * angle.sin(),
+                ],
+                &c.draw_state,
+                c.transform,
+                g,
+            );
+
+            if effects.shield > 0.0 {
+                Ellipse::new_border([0.4, 0.8, 1.0, 0.8], 1.5).draw(
+                    [x - 6.0, y - 6.0, width + 12.0, height + 12.0],
+                    &c.draw_state,
+                    c.transform,
+                    g,
+                );
+            }
         }
     }
 }
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Side of a portal tile entities enter and leave through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Facing {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Facing {
+    pub const ALL: [Facing; 4] = [Facing::Up, Facing::Down, Facing::Left, Facing::Right];
+
+    /// Grid offset of the cell in front of the portal.
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Facing::Up => (0, -1),
+            Facing::Down => (0, 1),
+            Facing::Left => (-1, 0),
+            Facing::Right => (1, 0),
+        }
+    }
+
+    fn angle(self) -> f64 {
+        match self {
+            Facing::Right => 0.0,
+            Facing::Down => PI / 2.0,
+            Facing::Left => PI,
+            Facing::Up => -PI / 2.0,
+        }
+    }
+}
+
+/// Where an entity leaves the partner portal and with which velocity.
+#[derive(Debug)]
+pub struct Transfer {
+    pub x: f64,
+    pub y: f64,
+    pub dx: f64,
+    pub dy: f64,
+}
+
+/// Rotates a velocity entering through `entry` so it leaves through `exit`,
+/// e.g. falling into a floor portal makes you fly out of a wall portal
+/// sideways.
+pub fn rotate(dx: f64, dy: f64, entry: Facing, exit: Facing) -> (f64, f64) {
+    let (sin, cos) = (exit.angle() - entry.angle() - PI).sin_cos();
+    (dx * cos - dy * sin, dx * sin + dy * cos)
+}
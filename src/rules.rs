@@ -0,0 +1,192 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Gameplay constants chosen by the host and shared with clients, so both
+/// sides simulate the same way.
+///
+/// Rule files only need to list the values that differ from the defaults.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub lives: u8,
+    pub speed: f64,
+    /// Speed while a speed boost is active.
+    pub boosted_speed: f64,
+    pub jump_speed: f64,
+    pub wall_jump_speed: f64,
+    pub double_jump_speed: f64,
+    pub gravity: f64,
+    pub ground_friction: f64,
+    pub ice_friction: f64,
+    pub air_friction: f64,
+    /// Factor applied to the shot speed of every weapon.
+    pub shot_speed: f64,
+    /// Time eliminated players wait before they respawn, if the game
+    /// respawns players.
+    pub respawn_delay: f64,
+    /// Time respawned players can't be hit.
+    pub spawn_invulnerability: f64,
+    /// What shots of the same player or team do when they run into each other.
+    pub friendly_shots: Interaction,
+    /// What shots of opponents do when they run into each other.
+    pub enemy_shots: Interaction,
+}
+
+/// What happens when two shots run into each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Interaction {
+    /// The shots pass through each other.
+    Pass,
+    /// Both shots are destroyed, grenades still explode.
+    Annihilate,
+    /// The shots bounce off each other like a wall hit, heavier weapons
+    /// pushing lighter ones away.
+    Deflect,
+    /// The heavier shot absorbs the other one and its momentum.
+    Merge,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            lives: 20,
+            speed: 300.0,
+            boosted_speed: 450.0,
+            jump_speed: 805.0,
+            wall_jump_speed: 705.0,
+            double_jump_speed: 405.0,
+            gravity: 1000.0,
+            ground_friction: 16.0,
+            ice_friction: 1.0,
+            air_friction: 4.0,
+            shot_speed: 1.0,
+            respawn_delay: 3.0,
+            spawn_invulnerability: 2.0,
+            friendly_shots: Interaction::Pass,
+            enemy_shots: Interaction::Annihilate,
+        }
+    }
+}
+
+impl Rules {
+    pub const PRESETS: [&'static str; 3] = ["default", "low-gravity", "one-shot"];
+
+    pub fn preset(name: &str) -> Option<Self> {
+        let rules = match name {
+            "default" => Self::default(),
+            "low-gravity" => Self {
+                gravity: 450.0,
+                jump_speed: 640.0,
+                wall_jump_speed: 560.0,
+                double_jump_speed: 320.0,
+                air_friction: 1.5,
+                ..Self::default()
+            },
+            "one-shot" => Self {
+                lives: 1,
+                ..Self::default()
+            },
+            _ => return None,
+        };
+        Some(rules)
+    }
+
+    /// Loads a preset by name or a rule file in toml format.
+    pub fn load(preset_or_path: &str) -> Result<Self, anyhow::Error> {
+        if let Some(rules) = Self::preset(preset_or_path) {
+            return Ok(rules);
+        }
+
+        let path = Path::new(preset_or_path);
+        let text = fs::read_to_string(path).map_err(|e| {
+            anyhow!(
+                "{} is neither a preset ({}) nor a readable file: {}",
+                preset_or_path,
+                Self::PRESETS.join(", "),
+                e
+            )
+        })?;
+        let rules: Self = toml::from_str(&text)?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Rejects values the game can't run with, naming the first bad field.
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.lives == 0 {
+            return Err(anyhow!("lives must be at least 1"));
+        }
+
+        let values = [
+            ("speed", self.speed),
+            ("boosted_speed", self.boosted_speed),
+            ("jump_speed", self.jump_speed),
+            ("wall_jump_speed", self.wall_jump_speed),
+            ("double_jump_speed", self.double_jump_speed),
+            ("gravity", self.gravity),
+            ("ground_friction", self.ground_friction),
+            ("ice_friction", self.ice_friction),
+            ("air_friction", self.air_friction),
+            ("shot_speed", self.shot_speed),
+            ("respawn_delay", self.respawn_delay),
+            ("spawn_invulnerability", self.spawn_invulnerability),
+        ];
+        for (name, value) in values.iter() {
+            if !value.is_finite() || *value < 0.0 {
+                return Err(anyhow!(
+                    "{} must be a finite number of at least 0, got {}",
+                    name,
+                    value
+                ));
+            }
+        }
+        // shots would stand still, and bots divide by it to lead targets
+        if self.shot_speed <= 0.0 {
+            return Err(anyhow!(
+                "shot_speed must be greater than 0, got {}",
+                self.shot_speed
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_valid() {
+        for name in Rules::PRESETS.iter() {
+            Rules::preset(name).unwrap().validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn invalid_values_are_rejected_by_name() {
+        let error = |text: &str| {
+            let rules: Rules = toml::from_str(text).unwrap();
+            rules.validate().unwrap_err().to_string()
+        };
+
+        assert!(error("lives = 0").contains("lives"));
+        assert!(error("gravity = -1.0").contains("gravity"));
+        assert!(error("shot_speed = nan").contains("shot_speed"));
+        assert!(error("speed = inf").contains("speed"));
+        assert!(error("shot_speed = 0.0").contains("shot_speed"));
+    }
+
+    #[test]
+    fn example_file_loads() {
+        Rules::load("rules/example.toml").unwrap();
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let error = toml::from_str::<Rules>("gravty = 400.0").unwrap_err();
+        assert!(error.to_string().contains("gravty"));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::entity::{Bounds, Speed};
+use crate::weapon::Weapon;
 use serde::{Deserialize, Serialize};
 
 #[derive(PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
@@ -15,6 +16,7 @@ pub struct State {
     pub dx: f64,
     pub dy: f64,
     pub lives: u32,
+    pub weapon: Weapon,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,12 +25,15 @@ pub struct Shot {
     pub w: f64,
     pub h: f64,
     pub color: [f32; 4],
+    /// Id of the first shot fired together with this one, shots of the same
+    /// volley never interact. Only known to the host.
+    #[serde(skip)]
+    pub volley: u32,
     pub dirty: bool,
 }
 
 impl Shot {
-    pub fn new(x: f64, y: f64, dx: f64, dy: f64, id: u32, owner: String, color: [f32; 4]) -> Self {
-        let id = Id { id, owner };
+    pub fn new(x: f64, y: f64, dx: f64, dy: f64, id: Id, weapon: Weapon, color: [f32; 4]) -> Self {
         Self::from_state(
             State {
                 id,
@@ -36,17 +41,20 @@ impl Shot {
                 y,
                 dx,
                 dy,
-                lives: 5,
+                lives: weapon.lives(),
+                weapon,
             },
             color,
         )
     }
 
     pub fn from_state(state: State, color: [f32; 4]) -> Self {
+        let size = state.weapon.size();
         Self {
+            volley: state.id.id,
             state,
-            w: 15.0,
-            h: 15.0,
+            w: size,
+            h: size,
             color,
             dirty: true,
         }
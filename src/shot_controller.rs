@@ -1,112 +1,628 @@
 use crate::cell::Cell;
 use crate::collision;
-use crate::collision::Collision;
 use crate::entity::Bounds;
+use crate::game;
+use crate::game::Hit;
+use crate::rules::{Interaction, Rules};
 use crate::shot;
+use crate::spatial_hash::SpatialHash;
+use crate::tile::Tile;
 use crate::{Map, PlayerController, Shot};
 use piston::input::GenericEvent;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicU32, Ordering};
 
+/// Most bounces a shot can make in a single tick.
+const MAX_BOUNCES: usize = 4;
+/// Side of the squares shots are bucketed in when checking them against
+/// each other.
+const SHOT_GRID_SIZE: f64 = 64.0;
+
 #[derive(Default)]
 pub struct ShotController {
     pub shots: HashMap<shot::Id, Shot>,
     pub next_id: AtomicU32,
+    /// Only the authoritative side spawns shots, others receive them.
+    pub authoritative: bool,
 }
 
 impl ShotController {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(authoritative: bool) -> Self {
+        Self {
+            authoritative,
+            ..Self::default()
+        }
     }
 
     pub fn event<E: GenericEvent>(
         &mut self,
-        map: &Map,
+        map: &mut Map,
+        game: &game::State,
+        rules: &Rules,
         player_controller: &mut PlayerController,
         e: &E,
     ) {
-        fn process_collision(shot: &mut Shot, cells: &[Cell], dt: f64) {
-            match collision::check(shot, cells, dt) {
-                Some(Collision::Side { x, y }) => {
-                    if x.is_some() {
-                        shot.state.dx = -shot.state.dx;
-                    }
-                    if y.is_some() {
-                        shot.state.dy = -shot.state.dy;
-                    }
-                    shot.state.lives -= 1;
-                    shot.dirty = true;
-                }
-                Some(Collision::Corner { .. }) => {
-                    shot.state.dx = -shot.state.dx;
-                    shot.state.dy = -shot.state.dy;
-                    shot.state.lives -= 1;
-                    shot.dirty = true;
-                }
-                _ => {}
-            }
-        }
-
         fn collides(a: [f64; 4], b: [f64; 4]) -> bool {
             a[0] < b[0] + b[2] && a[0] + a[2] > b[0] && a[1] < b[1] + b[3] && a[1] + a[3] > b[1]
         }
 
-        fn motion(shot: &mut Shot, dt: f64) {
-            shot.state.x += shot.state.dx * dt;
-            shot.state.y += shot.state.dy * dt;
-        }
-
         if let Some(tick) = e.update_args() {
-            self.update(map, player_controller);
+            self.update(map, rules, player_controller, tick.dt);
+            self.explode(map, game, player_controller);
 
+            let [width, height] = map.world_size();
             self.shots.retain(|_, shot| {
-                shot.state.lives > 0 && collides(shot.bounds(), [0.0, 0.0, 1920.0, 1080.0])
+                shot.state.lives > 0 && collides(shot.bounds(), [0.0, 0.0, width, height])
             });
 
-            let cells: Vec<_> = map.all_cells().collect();
-            for mut shot in self.shots.values_mut() {
-                process_collision(&mut shot, &cells, tick.dt);
-                motion(&mut shot, tick.dt);
+            let mut damaged = vec![];
+            for shot in self.shots.values_mut() {
+                let hits = advance(shot, map, tick.dt);
+                if hits.iter().any(|cell| cell.tile == Tile::Absorb) {
+                    shot.state.lives = 0;
+                }
+                if self.authoritative {
+                    let damage = shot.state.weapon.damage().max(1);
+                    damaged.extend(hits.iter().map(|cell| (center(cell), damage)));
+                }
+                if let Some(transfer) = map.portal_transfer(shot) {
+                    shot.state.x = transfer.x;
+                    shot.state.y = transfer.y;
+                    shot.state.dx = transfer.dx;
+                    shot.state.dy = transfer.dy;
+                    shot.dirty = true;
+                }
+            }
+
+            for ((x, y), damage) in damaged {
+                map.damage_at(x, y, damage);
+            }
+
+            if self.authoritative {
+                self.interact(game, rules, player_controller);
             }
         }
     }
 
-    fn update(&mut self, map: &Map, player_controller: &mut PlayerController) {
+    fn update(
+        &mut self,
+        map: &Map,
+        rules: &Rules,
+        player_controller: &mut PlayerController,
+        dt: f64,
+    ) {
         for player in player_controller.players.values_mut() {
             if player.state.lives == 0 {
                 continue;
             }
 
-            if player.inputs.shoot {
-                let player_x = player.state.x;
-                let player_y = player.state.y;
-                let mouse_x = player_x - player.inputs.mouse_x + 15.0 / 2.0;
-                let mouse_y = player_y - player.inputs.mouse_y + 15.0 / 2.0;
-                let angle = mouse_y.atan2(mouse_x);
-                let speed = 800.0;
-                let spawn_dist = 20.0;
-                let id = self.next_id.fetch_add(1, Ordering::SeqCst);
-                let shot = Shot::new(
-                    player_x + spawn_dist * -angle.cos(),
-                    player_y + spawn_dist * -angle.sin(),
-                    speed * -angle.cos(),
-                    speed * -angle.sin(),
-                    id,
-                    player.state.name.clone(),
-                    player.state.color,
-                );
-                let cells: Vec<_> = map.all_cells().collect();
-                if let Some(Collision::Side {
-                    x: Some(_),
-                    y: Some(_),
-                }) = collision::check(&shot, &cells, 0.0)
+            if player.ammo.tick(player.inputs.weapon, dt) {
+                player.ammo_dirty = true;
+            }
+
+            if self.authoritative
+                && player.inputs.shoot
+                && !player.state.carrying_flag
+                && player.ammo.can_fire()
+            {
+                let (player_x, player_y) = center(player);
+                let weapon = player.inputs.weapon;
+                let speed = weapon.speed() * rules.shot_speed;
+                let mut fired = false;
+                let mut volley = None;
+                let mut angles = weapon.spread(player.aim_angle());
+                if player.state.effects.triple_shot > 0.0 {
+                    angles = angles
+                        .into_iter()
+                        .flat_map(|angle| vec![angle - 0.15, angle, angle + 0.15])
+                        .collect();
+                }
+                for angle in angles {
+                    let id = shot::Id {
+                        id: self.next_id.fetch_add(1, Ordering::SeqCst),
+                        owner: player.state.name.clone(),
+                    };
+                    // spawn the shot centered on the aim line, just outside the player
+                    let (cos, sin) = (angle.cos(), angle.sin());
+                    let half_size = weapon.size() / 2.0;
+                    let spawn_dist =
+                        (player.state.width / 2.0 + half_size + 1.0) / cos.abs().max(sin.abs());
+                    let mut shot = Shot::new(
+                        player_x + spawn_dist * cos - half_size,
+                        player_y + spawn_dist * sin - half_size,
+                        speed * cos,
+                        speed * sin,
+                        id,
+                        weapon,
+                        player.state.color,
+                    );
+                    shot.volley = *volley.get_or_insert(shot.state.id.id);
+                    if player.state.effects.extra_bounces > 0.0 {
+                        shot.state.lives += 3;
+                    }
+                    let blocked = map
+                        .cells_in(&shot)
+                        .any(|cell| cell.tile.blocks_shots() && collision::collides(&shot, &cell))
+                        || map
+                            .platforms
+                            .iter()
+                            .any(|platform| collision::collides(&shot, platform));
+                    if blocked {
+                        continue;
+                    }
+                    self.shots.insert(shot.state.id.clone(), shot);
+                    fired = true;
+                }
+
+                if fired {
+                    player.inputs.shoot = false;
+                    player.ammo.fire();
+                    player.ammo_dirty = true;
+                }
+            }
+        }
+    }
+
+    /// Lets shots that ran into each other interact as the rules say for
+    /// shots of the same side or of opponents.
+    fn interact(
+        &mut self,
+        game: &game::State,
+        rules: &Rules,
+        player_controller: &PlayerController,
+    ) {
+        let team = |owner: &str| {
+            player_controller
+                .players
+                .get(owner)
+                .and_then(|player| player.state.team)
+        };
+
+        let mut grid = SpatialHash::new(SHOT_GRID_SIZE);
+        for (id, shot) in self.shots.iter().filter(|(_, shot)| shot.state.lives > 0) {
+            grid.insert(id.clone(), shot);
+        }
+
+        let mut done = HashSet::new();
+        let ids: Vec<_> = self.shots.keys().cloned().collect();
+        for id in ids {
+            done.insert(id.clone());
+            for other in grid.query(&self.shots[&id]) {
+                if done.contains(&other) {
+                    continue;
+                }
+                let (a, b) = (&self.shots[&id], &self.shots[&other]);
+                // pellets of one volley start out overlapping
+                let same_volley = id.owner == other.owner && a.volley == b.volley;
+                if a.state.lives == 0
+                    || b.state.lives == 0
+                    || same_volley
+                    || !collision::collides(a, b)
                 {
                     continue;
                 }
-                self.shots.insert(shot.state.id.clone(), shot);
 
-                player.inputs.shoot = false;
+                let friendly = id.owner == other.owner
+                    || (game.is_team_game()
+                        && team(&id.owner).is_some()
+                        && team(&id.owner) == team(&other.owner));
+                let interaction = if friendly {
+                    rules.friendly_shots
+                } else {
+                    rules.enemy_shots
+                };
+                if interaction == Interaction::Pass {
+                    continue;
+                }
+
+                let mut a = self.shots.remove(&id).unwrap();
+                let mut b = self.shots.remove(&other).unwrap();
+                collide(&mut a, &mut b, interaction);
+                self.shots.insert(id.clone(), a);
+                self.shots.insert(other, b);
             }
         }
     }
+
+    /// Applies area damage of shots that were destroyed this tick.
+    fn explode(
+        &mut self,
+        map: &mut Map,
+        game: &game::State,
+        player_controller: &mut PlayerController,
+    ) {
+        for shot in self.shots.values().filter(|shot| shot.state.lives == 0) {
+            let blast = match shot.state.weapon.blast() {
+                Some(blast) => blast,
+                None => continue,
+            };
+
+            let owner = &shot.state.id.owner;
+            let shooter_team = player_controller
+                .players
+                .get(owner)
+                .and_then(|player| player.state.team);
+            let (shot_x, shot_y) = center(shot);
+            let mut reflected = 0;
+            for player in player_controller.players.values_mut() {
+                if player.state.lives == 0 {
+                    continue;
+                }
+
+                let (player_x, player_y) = center(player);
+                if (player_x - shot_x).hypot(player_y - shot_y) > blast.radius {
+                    continue;
+                }
+                match game.resolve_hit(owner, shooter_team, &player.state) {
+                    Hit::Target => {
+                        player.last_attacker = Some(owner.clone());
+                        player.hit(blast.damage);
+                    }
+                    Hit::Shooter => reflected += blast.damage,
+                    Hit::Nobody => {}
+                }
+            }
+            if let Some(shooter) = player_controller.players.get_mut(owner) {
+                if reflected > 0 {
+                    shooter.hit(reflected);
+                }
+            }
+
+            if self.authoritative {
+                let area = [
+                    shot_x - blast.radius,
+                    shot_y - blast.radius,
+                    blast.radius * 2.0,
+                    blast.radius * 2.0,
+                ];
+                let cells: Vec<_> = map
+                    .cells_in(&area)
+                    .map(|cell| center(&cell))
+                    .filter(|(x, y)| (x - shot_x).hypot(y - shot_y) <= blast.radius)
+                    .collect();
+                for (x, y) in cells {
+                    map.damage_at(x, y, blast.damage);
+                }
+            }
+        }
+    }
+}
+
+/// Resolves two shots running into each other, heavier weapons win merges and
+/// push lighter shots away.
+fn collide(a: &mut Shot, b: &mut Shot, interaction: Interaction) {
+    let (ma, mb) = (a.state.weapon.mass(), b.state.weapon.mass());
+    match interaction {
+        Interaction::Pass => return,
+        Interaction::Annihilate => {
+            a.state.lives = 0;
+            b.state.lives = 0;
+        }
+        Interaction::Deflect => {
+            let (ax, ay) = center(a);
+            let (bx, by) = center(b);
+            let distance = (bx - ax).hypot(by - ay).max(f64::EPSILON);
+            let (nx, ny) = ((bx - ax) / distance, (by - ay) / distance);
+            let va = a.state.dx * nx + a.state.dy * ny;
+            let vb = b.state.dx * nx + b.state.dy * ny;
+            if va <= vb {
+                // already moving apart
+                return;
+            }
+
+            // elastic collision along the line between the centers
+            let va_after = ((ma - mb) * va + 2.0 * mb * vb) / (ma + mb);
+            let vb_after = ((mb - ma) * vb + 2.0 * ma * va) / (ma + mb);
+            a.state.dx += (va_after - va) * nx;
+            a.state.dy += (va_after - va) * ny;
+            b.state.dx += (vb_after - vb) * nx;
+            b.state.dy += (vb_after - vb) * ny;
+            a.state.lives -= 1;
+            b.state.lives -= 1;
+        }
+        Interaction::Merge => {
+            let dx = (ma * a.state.dx + mb * b.state.dx) / (ma + mb);
+            let dy = (ma * a.state.dy + mb * b.state.dy) / (ma + mb);
+            let lives = a.state.lives.max(b.state.lives);
+            let (survivor, absorbed) = if mb > ma {
+                (&mut *b, &mut *a)
+            } else {
+                (&mut *a, &mut *b)
+            };
+            survivor.state.dx = dx;
+            survivor.state.dy = dy;
+            survivor.state.lives = lives;
+            absorbed.state.lives = 0;
+        }
+    }
+    a.dirty = true;
+    b.dirty = true;
+}
+
+/// Moves the shot, bouncing it off the walls and platforms in its way,
+/// and returns the cells it hit.
+fn advance(shot: &mut Shot, map: &Map, dt: f64) -> Vec<Cell> {
+    shot.state.dy += shot.state.weapon.gravity() * dt;
+
+    let mut hits: Vec<Cell> = vec![];
+    let mut remaining = dt;
+    let mut first = true;
+    for _ in 0..MAX_BOUNCES {
+        let cells: Vec<_> = map
+            .cells_in(&collision::swept_bounds(shot, remaining))
+            .filter(|cell| cell.tile.blocks_shots())
+            .collect();
+        let impact = match collision::sweep(shot, &cells, &map.platforms, remaining) {
+            Some(impact) => impact,
+            None => {
+                shot.state.x += shot.state.dx * remaining;
+                shot.state.y += shot.state.dy * remaining;
+                break;
+            }
+        };
+
+        shot.state.x = impact.x;
+        shot.state.y = impact.y;
+        let (dx, dy) = collision::reflect(shot.state.dx, shot.state.dy, impact.normal);
+        shot.state.dx = dx;
+        shot.state.dy = dy;
+        // hitting a wall and a floor at the same instant is one bounce
+        if impact.time > 0.0 || first {
+            shot.state.lives -= 1;
+        }
+        first = false;
+        shot.dirty = true;
+        if let Some(&cell) = impact.obstacle.cell() {
+            if !hits.iter().any(|hit| (hit.x, hit.y) == (cell.x, cell.y)) {
+                hits.push(cell);
+            }
+        }
+        if shot.state.lives == 0 {
+            break;
+        }
+        remaining *= 1.0 - impact.time;
+    }
+    hits
+}
+
+fn center<B: Bounds>(b: &B) -> (f64, f64) {
+    (b.x() + b.w() / 2.0, b.y() + b.h() / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::Ammo;
+    use crate::weapon::Weapon;
+    use crate::Player;
+    use piston::input::{Event, Loop, UpdateArgs};
+    use std::time::{Duration, Instant};
+
+    fn bolt(x: f64, y: f64, dx: f64, dy: f64) -> Shot {
+        let id = shot::Id {
+            id: 0,
+            owner: "test".to_string(),
+        };
+        Shot::new(x, y, dx, dy, id, Weapon::Bolt, [1.0; 4])
+    }
+
+    /// Time a tick takes per shot with `count` shots bouncing around a closed
+    /// map, the best of a few runs to filter out noise.
+    fn tick_time_per_shot(count: usize) -> Duration {
+        const TICKS: u32 = 20;
+
+        let (width, height) = (48, 27);
+        let wall = "#".repeat(width);
+        let inner = format!("#{}#", ".".repeat(width - 2));
+        let mut rows = vec![wall.clone()];
+        rows.extend(std::iter::repeat_n(inner, height - 2));
+        rows.push(wall);
+
+        let game = game::State::default();
+        // shots of different players keep bouncing off each other
+        let rules = Rules {
+            enemy_shots: Interaction::Deflect,
+            ..Rules::default()
+        };
+        let tick = Event::Loop(Loop::Update(UpdateArgs { dt: 1.0 / 60.0 }));
+
+        (0..3)
+            .map(|_| {
+                let mut map = Map::parse(&rows.join("\n")).unwrap();
+                let mut player_controller = PlayerController::new();
+                let mut controller = ShotController::new(true);
+                for i in 0..count {
+                    let x = 60.0 + (i * 37 % 1700) as f64;
+                    let y = 60.0 + (i * 53 % 900) as f64;
+                    let angle = i as f64;
+                    let mut shot = bolt(x, y, 300.0 * angle.cos(), 300.0 * angle.sin());
+                    shot.state.id = shot::Id {
+                        id: i as u32,
+                        owner: format!("player {}", i % 4),
+                    };
+                    shot.state.lives = u32::MAX;
+                    controller.shots.insert(shot.state.id.clone(), shot);
+                }
+
+                let start = Instant::now();
+                for _ in 0..TICKS {
+                    controller.event(&mut map, &game, &rules, &mut player_controller, &tick);
+                }
+                start.elapsed() / TICKS / count as u32
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn tick_time_per_shot_stays_flat_with_hundreds_of_shots() {
+        let base = tick_time_per_shot(100);
+        for &count in &[300, 1000] {
+            let time = tick_time_per_shot(count);
+            // checking every pair of shots would make this ten times slower
+            assert!(
+                time < base * 4,
+                "{:?} per shot with {} shots, {:?} with 100",
+                time,
+                count,
+                base
+            );
+        }
+    }
+
+    /// Map with the given rows in its top left corner, padded with empty
+    /// cells to the default 48x27 grid so cells are 40 px wide.
+    fn crafted_map(rows: &[&str]) -> Map {
+        let text: Vec<String> = (0..27)
+            .map(|y| format!("{:.<48}", rows.get(y).copied().unwrap_or("")))
+            .collect();
+        Map::parse(&text.join("\n")).unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {} but got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn bounce_uses_up_the_remaining_travel() {
+        let map = crafted_map(&[".....", "....#", "....."]);
+        let mut shot = bolt(105.0, 50.0, 400.0, 0.0);
+        let hits = advance(&mut shot, &map, 0.2);
+
+        // 40 px to the wall, then the other 40 px back
+        assert_close(shot.state.x, 105.0);
+        assert_close(shot.state.y, 50.0);
+        assert_close(shot.state.dx, -400.0);
+        assert_eq!(shot.state.lives, Weapon::Bolt.lives() - 1);
+        assert_eq!(hits.len(), 1);
+        assert_close(hits[0].x, 160.0);
+    }
+
+    #[test]
+    fn wall_and_floor_at_the_same_instant_cost_one_life() {
+        let map = crafted_map(&["...#", "...#", "####"]);
+        let mut shot = bolt(65.0, 25.0, 400.0, 400.0);
+        advance(&mut shot, &map, 0.2);
+
+        assert_close(shot.state.dx, -400.0);
+        assert_close(shot.state.dy, -400.0);
+        assert_close(shot.state.x, 65.0);
+        assert_close(shot.state.y, 25.0);
+        assert_eq!(shot.state.lives, Weapon::Bolt.lives() - 1);
+    }
+
+    #[test]
+    fn bounces_are_limited_per_tick() {
+        // a narrow corridor the shot would cross many times in one tick
+        let map = crafted_map(&["#.#", "#.#"]);
+        let mut shot = bolt(50.0, 10.0, 4000.0, 0.0);
+        shot.state.lives = 100;
+        advance(&mut shot, &map, 0.5);
+
+        assert_eq!(shot.state.lives, 100 - MAX_BOUNCES as u32);
+        assert!(shot.state.x >= 40.0 && shot.state.x + shot.w <= 80.0);
+    }
+
+    /// Distance from `point` to the segment between `a` and `b`.
+    fn segment_distance(point: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+        let (abx, aby) = (b.0 - a.0, b.1 - a.1);
+        let length = abx * abx + aby * aby;
+        let t = if length > 0.0 {
+            (((point.0 - a.0) * abx + (point.1 - a.1) * aby) / length).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        (point.0 - a.0 - t * abx).hypot(point.1 - a.1 - t * aby)
+    }
+
+    #[test]
+    fn shots_pass_through_the_aimed_point() {
+        let map = crafted_map(&[]);
+        let rules = Rules::default();
+        // the player's center is at (600, 600); two angles inside each octant
+        // plus the axes and diagonals between them
+        let angles = (0..16).map(|i| f64::from(i) * std::f64::consts::PI / 8.0 + 0.1);
+        let axes = (0..8).map(|i| f64::from(i) * std::f64::consts::FRAC_PI_4);
+
+        for angle in angles.chain(axes) {
+            for &distance in &[60.0, 250.0] {
+                for &weapon in &[Weapon::Bolt, Weapon::Sniper] {
+                    let target = (
+                        600.0 + distance * angle.cos(),
+                        600.0 + distance * angle.sin(),
+                    );
+                    let mut player = Player::new("a".to_string(), 590.0, 590.0, [1.0; 4], 3);
+                    player.inputs.shoot = true;
+                    player.inputs.weapon = weapon;
+                    player.ammo = Ammo::new(weapon);
+                    player.inputs.mouse_x = target.0;
+                    player.inputs.mouse_y = target.1;
+                    let mut player_controller = PlayerController::new();
+                    player_controller.players.insert("a".to_string(), player);
+
+                    let mut controller = ShotController::new(true);
+                    controller.update(&map, &rules, &mut player_controller, 0.0);
+                    assert_eq!(controller.shots.len(), 1);
+                    let shot = controller.shots.values_mut().next().unwrap();
+
+                    let mut closest = f64::INFINITY;
+                    for _ in 0..30 {
+                        let start = center(&*shot);
+                        advance(shot, &map, 1.0 / 60.0);
+                        let end = center(&*shot);
+                        closest = closest.min(segment_distance(target, start, end));
+                    }
+                    assert!(
+                        closest < 1e-6,
+                        "{:?} aimed at {:?} missed by {}",
+                        weapon,
+                        target,
+                        closest
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pellets_of_one_volley_leave_each_other_alone() {
+        let map = crafted_map(&[]);
+        let game = game::State::default();
+        let rules = Rules {
+            friendly_shots: Interaction::Annihilate,
+            ..Rules::default()
+        };
+        let mut player = Player::new("a".to_string(), 190.0, 190.0, [1.0; 4], 3);
+        player.inputs.shoot = true;
+        player.inputs.weapon = Weapon::Shotgun;
+        player.ammo = Ammo::new(Weapon::Shotgun);
+        player.inputs.mouse_x = 400.0;
+        player.inputs.mouse_y = 200.0;
+        let mut player_controller = PlayerController::new();
+        player_controller.players.insert("a".to_string(), player);
+
+        let mut controller = ShotController::new(true);
+        controller.update(&map, &rules, &mut player_controller, 0.0);
+        let pellets = controller.shots.len();
+        assert!(pellets > 1);
+        controller.interact(&game, &rules, &player_controller);
+        assert!(controller.shots.values().all(|shot| shot.state.lives > 0));
+
+        // a second volley into the first one still annihilates
+        let mut other = bolt(0.0, 0.0, -800.0, 0.0);
+        let first = controller.shots.values().next().unwrap();
+        other.state.x = first.state.x;
+        other.state.y = first.state.y;
+        other.state.id.owner = "a".to_string();
+        other.state.id.id = 1000;
+        other.volley = 1000;
+        controller.shots.insert(other.state.id.clone(), other);
+        controller.interact(&game, &rules, &player_controller);
+        assert!(controller.shots.values().any(|shot| shot.state.lives == 0));
+    }
 }
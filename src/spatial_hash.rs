@@ -0,0 +1,73 @@
+use crate::entity::Bounds;
+use std::collections::HashMap;
+
+/// Buckets entities by the squares of a coarse grid their bounds overlap, so
+/// checks between entities only look at the ones nearby.
+pub struct SpatialHash<K> {
+    size: f64,
+    buckets: HashMap<(i32, i32), Vec<K>>,
+}
+
+impl<K: Clone + PartialEq> SpatialHash<K> {
+    pub fn new(size: f64) -> Self {
+        Self {
+            size,
+            buckets: HashMap::new(),
+        }
+    }
+
+    pub fn insert<B: Bounds>(&mut self, key: K, bounds: &B) {
+        for square in self.squares(bounds) {
+            self.buckets.entry(square).or_default().push(key.clone());
+        }
+    }
+
+    /// Keys of the entities sharing a square with `bounds`, each listed once.
+    /// They are only candidates and still need an exact check.
+    pub fn query<B: Bounds>(&self, bounds: &B) -> Vec<K> {
+        let mut found: Vec<K> = vec![];
+        for square in self.squares(bounds) {
+            for key in self.buckets.get(&square).into_iter().flatten() {
+                if !found.contains(key) {
+                    found.push(key.clone());
+                }
+            }
+        }
+        found
+    }
+
+    fn squares<B: Bounds>(&self, bounds: &B) -> impl Iterator<Item = (i32, i32)> {
+        let x0 = (bounds.x() / self.size).floor() as i32;
+        let y0 = (bounds.y() / self.size).floor() as i32;
+        let x1 = ((bounds.x() + bounds.w()) / self.size).floor() as i32;
+        let y1 = ((bounds.y() + bounds.h()) / self.size).floor() as i32;
+        (x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_lists_each_overlapping_key_once() {
+        let mut hash = SpatialHash::new(10.0);
+        // spans four squares
+        hash.insert(1, &[5.0, 5.0, 10.0, 10.0]);
+        // spans two squares, both shared with the first
+        hash.insert(2, &[12.0, 2.0, 5.0, 5.0]);
+        hash.insert(3, &[100.0, 100.0, 5.0, 5.0]);
+
+        let mut found = hash.query(&[0.0, 0.0, 20.0, 20.0]);
+        found.sort_unstable();
+        assert_eq!(found, vec![1, 2]);
+    }
+
+    #[test]
+    fn query_handles_negative_coordinates() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.insert("a", &[-15.0, -15.0, 10.0, 10.0]);
+        assert_eq!(hash.query(&[-8.0, -8.0, 2.0, 2.0]), vec!["a"]);
+        assert!(hash.query(&[2.0, 2.0, 2.0, 2.0]).is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+use crate::portal::Facing;
+use serde::{Deserialize, Serialize};
+
+/// Hits a destructible wall takes before it breaks.
+pub const DESTRUCTIBLE_HP: u8 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tile {
+    Empty,
+    Solid,
+    Destructible {
+        hp: u8,
+    },
+    /// Platform that can be jumped through from below, shots pass through.
+    OneWay,
+    /// Launches players that land on it.
+    Bounce,
+    /// Hurts players that touch it.
+    Spikes,
+    /// Floor with barely any friction.
+    Ice,
+    /// Wall that destroys shots instead of bouncing them.
+    Absorb,
+    /// Sends players and shots to the other portal with the same `pair`.
+    Portal {
+        pair: u8,
+        facing: Facing,
+    },
+}
+
+impl Tile {
+    pub fn is_empty(self) -> bool {
+        self == Tile::Empty
+    }
+
+    /// Whether players can stand on the tile.
+    pub fn is_floor(self) -> bool {
+        !matches!(self, Tile::Empty | Tile::Spikes | Tile::Portal { .. })
+    }
+
+    /// Whether players collide with the tile from all sides.
+    pub fn blocks_players(self) -> bool {
+        !matches!(self, Tile::Empty | Tile::OneWay | Tile::Portal { .. })
+    }
+
+    pub fn blocks_shots(self) -> bool {
+        !matches!(self, Tile::Empty | Tile::OneWay | Tile::Portal { .. })
+    }
+
+    /// Returns the tile left after taking `damage`, or `None` if this tile
+    /// can't be damaged.
+    pub fn damaged(self, damage: u8) -> Option<Tile> {
+        match self {
+            Tile::Destructible { hp } if hp > damage => {
+                Some(Tile::Destructible { hp: hp - damage })
+            }
+            Tile::Destructible { .. } => Some(Tile::Empty),
+            _ => None,
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Tile> {
+        let tile = match c {
+            '.' | ' ' => Tile::Empty,
+            '#' => Tile::Solid,
+            '%' => Tile::Destructible {
+                hp: DESTRUCTIBLE_HP,
+            },
+            '-' => Tile::OneWay,
+            '^' => Tile::Bounce,
+            'x' => Tile::Spikes,
+            '~' => Tile::Ice,
+            '@' => Tile::Absorb,
+            // facing is derived from the surrounding cells once the map is known
+            'A'..='Z' => Tile::Portal {
+                pair: c as u8 - b'A',
+                facing: Facing::Up,
+            },
+            _ => return None,
+        };
+        Some(tile)
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Tile::Empty => '.',
+            Tile::Solid => '#',
+            Tile::Destructible { .. } => '%',
+            Tile::OneWay => '-',
+            Tile::Bounce => '^',
+            Tile::Spikes => 'x',
+            Tile::Ice => '~',
+            Tile::Absorb => '@',
+            Tile::Portal { pair, .. } => char::from(b'A' + pair),
+        }
+    }
+}
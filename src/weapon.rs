@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Weapon {
+    /// All-round shot that bounces off walls a few times.
+    #[default]
+    Bolt,
+    /// Fast and hard hitting, but stops at the first wall.
+    Sniper,
+    /// Fires a spread of small pellets.
+    Shotgun,
+    /// Slow, falls with gravity and explodes when it runs out of bounces.
+    Grenade,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Blast {
+    pub radius: f64,
+    pub damage: u8,
+}
+
+impl Weapon {
+    pub const ALL: [Weapon; 4] = [
+        Weapon::Bolt,
+        Weapon::Sniper,
+        Weapon::Shotgun,
+        Weapon::Grenade,
+    ];
+
+    pub fn speed(self) -> f64 {
+        match self {
+            Weapon::Bolt => 800.0,
+            Weapon::Sniper => 1600.0,
+            Weapon::Shotgun => 700.0,
+            Weapon::Grenade => 550.0,
+        }
+    }
+
+    /// Minimum time between two shots in seconds.
+    pub fn cooldown(self) -> f64 {
+        match self {
+            Weapon::Bolt => 0.25,
+            Weapon::Sniper => 1.0,
+            Weapon::Shotgun => 0.7,
+            Weapon::Grenade => 1.2,
+        }
+    }
+
+    /// Shots that can be fired before reloading.
+    pub fn magazine(self) -> u32 {
+        match self {
+            Weapon::Bolt => 6,
+            Weapon::Sniper => 2,
+            Weapon::Shotgun => 3,
+            Weapon::Grenade => 2,
+        }
+    }
+
+    pub fn reload_time(self) -> f64 {
+        match self {
+            Weapon::Bolt => 1.5,
+            Weapon::Sniper => 2.5,
+            Weapon::Shotgun => 2.0,
+            Weapon::Grenade => 3.0,
+        }
+    }
+
+    /// Number of wall hits a shot survives, the last one destroys it.
+    pub fn lives(self) -> u32 {
+        match self {
+            Weapon::Bolt => 5,
+            Weapon::Sniper => 1,
+            Weapon::Shotgun => 2,
+            Weapon::Grenade => 4,
+        }
+    }
+
+    pub fn size(self) -> f64 {
+        match self {
+            Weapon::Bolt => 15.0,
+            Weapon::Sniper => 8.0,
+            Weapon::Shotgun => 9.0,
+            Weapon::Grenade => 18.0,
+        }
+    }
+
+    /// Weight in collisions with other shots.
+    pub fn mass(self) -> f64 {
+        match self {
+            Weapon::Bolt => 1.0,
+            Weapon::Sniper => 2.0,
+            Weapon::Shotgun => 0.5,
+            Weapon::Grenade => 3.0,
+        }
+    }
+
+    pub fn gravity(self) -> f64 {
+        match self {
+            Weapon::Grenade => 900.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Lives taken from a player hit directly.
+    pub fn damage(self) -> u8 {
+        match self {
+            Weapon::Bolt | Weapon::Shotgun => 1,
+            Weapon::Sniper => 3,
+            Weapon::Grenade => 0,
+        }
+    }
+
+    /// Area damage dealt when the shot is destroyed.
+    pub fn blast(self) -> Option<Blast> {
+        match self {
+            Weapon::Grenade => Some(Blast {
+                radius: 120.0,
+                damage: 4,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Directions of all shots fired at once when aiming at `angle`.
+    pub fn spread(self, angle: f64) -> Vec<f64> {
+        match self {
+            Weapon::Shotgun => {
+                let pellets = 5;
+                let spread = 0.4;
+                (0..pellets)
+                    .map(|i| angle + spread * (f64::from(i) / f64::from(pellets - 1) - 0.5))
+                    .collect()
+            }
+            _ => vec![angle],
+        }
+    }
+}